@@ -1,16 +1,65 @@
-use clap::Parser;
+use anyhow::{ Context, Result };
+use clap::parser::ValueSource;
+use clap::{ ArgAction, CommandFactory, Parser, ValueEnum };
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::template;
 
 #[derive(Parser, Debug)]
 #[command(
     author = "1060290944@qq.com",
     version = "0.0.1",
-    about = "Generate TypeScript services from OpenAPI/Swagger JSON",
-    long_about = None
+    about = "Generate TypeScript services from OpenAPI/Swagger JSON or YAML",
+    long_about = None,
+    args_override_self = true
 )]
 pub struct Args {
-    /// Path to swagger/openapi JSON file
+    /// Config file with default options (default: ./.ropenapirc.json when present)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<String>,
+
+    /// Path or http(s) URL of the swagger/openapi JSON or YAML file (repeatable, services of all
+    /// specs are merged by tag)
+    #[arg(short, long, required = true)]
+    pub swagger: Vec<String>,
+
+    /// Extra HTTP header sent when fetching a remote spec, e.g. 'Authorization: Bearer xxx' (repeatable)
+    #[arg(long, value_name = "NAME: VALUE")]
+    pub header: Vec<String>,
+
+    /// Parse the spec incrementally from the input stream instead of reading its whole text
+    /// first, skipping unused top-level sections
+    #[arg(long)]
+    pub streaming: bool,
+
+    /// Parse the spec and print the files that would be written, with a diff against
+    /// existing files, without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Remove the project output directory before writing, dropping files of removed services
+    #[arg(long)]
+    pub clean: bool,
+
+    /// Print every generated file to standard output, after a `// File: Name.ts` line,
+    /// instead of writing it
+    #[arg(long, conflicts_with_all = ["dry_run", "watch"])]
+    pub stdout: bool,
+
+    /// Keep running and regenerate whenever the spec or a file it `$ref`s changes. Local files
+    /// are polled for modification time changes every 500ms, remote specs are re-fetched every 5s
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Report JSON syntax errors with the file, line, column and the offending snippet
+    #[arg(long)]
+    pub pretty_json_errors: bool,
+
+    /// Report details of the run on standard error, such as the files read to resolve `$ref`s
     #[arg(short, long)]
-    pub swagger: String,
+    pub verbose: bool,
 
     /// Output directory (default: services/)
     #[arg(short, long)]
@@ -23,20 +72,489 @@ pub struct Args {
     /// Optional comma-separated tags to generate (services). If omitted, all tags are generated.
     #[arg(short, long)]
     pub tags: Option<String>,
-    /// Request lib path to import in generated services, e.g., 'import { request} from @/utils/request'
-    #[arg(short, long, default_value = "import { request } from '@/services/request';")]
+    /// Comma-separated tags to skip, applied after --tags
+    #[arg(long)]
+    pub exclude_tags: Option<String>,
+    /// Emit operations with several tags into the service of each tag, not just the first
+    #[arg(long)]
+    pub multi_tag: bool,
+    /// Name services after the tag in PascalCase (`UserAccountController.ts`) instead of lowercasing it
+    /// (`UseraccountController.ts`); tags are still grouped and filtered case-insensitively
+    #[arg(long)]
+    pub preserve_tag_case: bool,
+    /// Request lib path to import in generated services, e.g., 'import { request} from @/utils/request'.
+    /// Defaults to "import { request } from '@/services/request';", or an `axiosInstance` import with `--client axios`
+    #[arg(short, long)]
     pub request_lib_path: Option<String>,
     /// Project name, used for service folder name
     #[arg(short, long, default_value = "project-swagger")]
     pub project_name: Option<String>,
-    /// Api prefix, prefix of all api urls, eg. /api
+    /// Serialize query params into the URL (`?page=1&tag=a&tag=b`) instead of passing `params`
+    #[arg(long)]
+    pub query_string: bool,
+    /// Generic type wrapping every response, e.g. `IResponse` to return `Promise<IResponse<User>>`
+    #[arg(long, value_name = "TYPE")]
+    pub response_wrapper: Option<String>,
+    /// Module the --response-wrapper type is imported from (default: the request library's module)
+    #[arg(long, value_name = "MODULE", requires = "response_wrapper")]
+    pub response_wrapper_from: Option<String>,
+    /// Number of spaces per indentation level in the generated files
+    #[arg(long, value_name = "N", default_value_t = 2, value_parser = clap::value_parser!(u8).range(1..=8))]
+    pub indent: u8,
+    /// Naming style of the service file names
+    #[arg(long, value_enum, default_value_t = FileCase::Pascal)]
+    pub file_case: FileCase,
+    /// Api prefix, prefix of all api urls, eg. /api (default: path of servers[0].url or basePath)
     #[arg(short, long)]
     pub api_prefix: Option<String>,
-    // Namespace, All declarations will be wrapped in this namespace
-    // #[arg(short, long)]
-    // pub namespace: Option<String>,
+    /// Inline object types with fewer than N fields at their reference site instead of naming them
+    #[arg(long, value_name = "N")]
+    pub inline_small_types: Option<usize>,
+    /// Export a `routes` map of operation paths from each service file
+    #[arg(long)]
+    pub routes: bool,
+    /// Map numbers with minimum/maximum constraints to branded range types, e.g. `number & { __min: 0 }`
+    #[arg(long)]
+    pub branded_ranges: bool,
+    /// Source used to derive generated function names
+    #[arg(long, value_enum, default_value_t = OperationNameSource::OperationId)]
+    pub operation_name_from: OperationNameSource,
+    /// Send an `Authorization: Bearer <token>` header with operations requiring `security`
+    #[arg(long)]
+    pub auth: bool,
+    /// Expression evaluating to the bearer token sent with `--auth`, made available through `--request-lib-path`
+    #[arg(long, value_name = "EXPR", default_value = "getToken()", requires = "auth")]
+    pub auth_token: String,
+    /// Generate `pollXxx` helpers for long-running operations (`x-long-running` or `202` responses)
+    #[arg(long)]
+    pub polling: bool,
+    /// Export response examples from the spec as `xxxExample` fixture constants
+    #[arg(long)]
+    pub fixtures: bool,
+    /// How named enum schemas are emitted: literal unions or TS `enum` declarations
+    #[arg(long, value_enum, default_value_t = EnumStyle::Union)]
+    pub enum_style: EnumStyle,
+    /// Declare object schemas as `type` aliases or `interface`s
+    #[arg(long, value_enum, default_value_t = TypeStyle::Type)]
+    pub type_style: TypeStyle,
+    /// Type of object schemas without properties or `additionalProperties`
+    #[arg(long, value_enum, default_value_t = EmptyObject::Any)]
+    pub empty_object: EmptyObject,
+    /// Also export a const object of the values of each literal union enum
+    #[arg(long)]
+    pub enum_consts: bool,
+    /// Declare each type as a Zod schema and infer the TypeScript type from it
+    #[arg(long)]
+    pub zod: bool,
+    /// Export a `mockXxx()` factory per type returning a plausible value of it
+    #[arg(long)]
+    pub mock: bool,
+    /// Fill optional fields in the mock factories instead of leaving them out
+    #[arg(long, requires = "mock")]
+    pub mock_optional: bool,
+    /// Export an `isXxx(value): value is Xxx` guard per object type checking its required fields
+    #[arg(long)]
+    pub type_guards: bool,
+    /// How generated functions take their arguments
+    #[arg(long, value_enum, default_value_t = ArgsStyle::Positional)]
+    pub args_style: ArgsStyle,
+    /// Type request bodies as `Omit<T, ...>` of their `readOnly` fields
+    #[arg(long)]
+    pub omit_readonly_in_request: bool,
+    /// Type responses as `Omit<T, ...>` of their `writeOnly` fields
+    #[arg(long)]
+    pub omit_writeonly_in_response: bool,
+    /// camelCase snake_case and kebab-case field names of the declared types (types only,
+    /// the wire names are kept in a comment and have to be converted at runtime)
+    #[arg(long)]
+    pub camel_fields: bool,
+    /// Type responses as the union of every 2xx response type instead of the 200/201 one
+    #[arg(long)]
+    pub union_success_responses: bool,
+    /// Export a `XxxError` type per operation, the union of its typed 4xx/5xx responses, documented with `@throws`
+    #[arg(long)]
+    pub error_types: bool,
+    /// Generate `parseXxxParams(search: URLSearchParams)` parsers for GET operations
+    #[arg(long)]
+    pub search_parsers: bool,
+    /// Fail when generated function names collide instead of suffixing them,
+    /// checking within each service (default) or across all services
+    #[arg(long, value_enum, value_name = "SCOPE", num_args = 0..=1, default_missing_value = "service")]
+    pub validate_unique_operation_names: Option<NameScope>,
+    /// Type emitted for `format: date-time` / `date` strings
+    #[arg(long, value_enum, default_value_t = DateType::String)]
+    pub date_type: DateType,
+    /// Type emitted for `integer` schemas with `format: int64`
+    #[arg(long, value_enum, default_value_t = Int64Type::Number)]
+    pub int64: Int64Type,
+    /// Render and write service files in parallel
+    #[arg(long)]
+    pub parallel: bool,
+    /// Number of worker threads used with --parallel (default: number of CPUs)
+    #[arg(long, value_name = "N", requires = "parallel")]
+    pub jobs: Option<usize>,
+    /// Write an `index.ts` re-exporting every generated service
+    #[arg(long)]
+    pub index: bool,
+    /// Write a `types.ts` barrel re-exporting only the type declarations of every service
+    #[arg(long, conflicts_with = "shared_types")]
+    pub emit_barrel_types: bool,
+    /// Write a `manifest.json` listing every service with its file, operation count and types
+    #[arg(long)]
+    pub manifest: bool,
+    /// Declare every type once in a shared `types.ts` imported by the service files
+    #[arg(long)]
+    pub shared_types: bool,
+    /// HTTP client the functions send requests with, and/or data-fetching library to generate
+    /// hooks for in addition to the raw functions (comma-separated, e.g. `axios,react-query`)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub client: Vec<Client>,
+    /// Also emit a client for another runtime, typed from the generated services
+    #[arg(long, value_enum)]
+    pub compat: Option<Compat>,
+    /// Add the spec method and path (`GET /users/{id}`) to the JSDoc of every function
+    #[arg(long)]
+    pub source_comments: bool,
+    /// Add an optional `signal?: AbortSignal` argument to every function, passed to the request
+    #[arg(long)]
+    pub abort_signal: bool,
+    /// Template file of each generated function, with `{{name}}`, `{{params}}`, `{{body}}`, ...
+    /// placeholders. Only the function text is templated, not the types; not supported with
+    /// `--client angular`
+    #[arg(long, value_name = "PATH", value_parser = template::load)]
+    pub template: Option<String>,
+    /// Namespace, All declarations will be wrapped in this namespace
+    #[arg(long)]
+    pub namespace: Option<String>,
 }
 
-pub fn parse_args() -> Args {
-    Args::parse()
+/// Where generated function names come from
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OperationNameSource {
+    /// Use `operationId`, falling back to method + path
+    #[default]
+    #[value(name = "operationId")]
+    OperationId,
+    /// CamelCase the operation `summary`, falling back to `operationId` and then method + path
+    Summary,
+    /// Always derive names from method + path
+    Path,
+}
+
+/// How named enum schemas are emitted
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnumStyle {
+    /// `export type Status = 'ACTIVE' | 'INACTIVE';`
+    #[default]
+    Union,
+    /// `export enum Status { ACTIVE = 'ACTIVE', INACTIVE = 'INACTIVE' }`
+    Enum,
+}
+
+/// How generated functions take their path params, `params` and `data`
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArgsStyle {
+    /// `getUser(id, params)`
+    #[default]
+    Positional,
+    /// `getUser({ id, params })`, typed by a generated `GetUserArgs` object type
+    Object,
+}
+
+/// How object schemas are declared
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TypeStyle {
+    /// `export type User = { ... }`
+    #[default]
+    Type,
+    /// `export interface User { ... }`, unions and other non-object types stay aliases
+    Interface,
+}
+
+/// How object schemas without any declared property are typed
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmptyObject {
+    /// `export type Metadata = any`
+    #[default]
+    Any,
+    /// `export type Metadata = Record<string, any>`
+    Record,
+}
+
+/// Scope in which generated function names must be unique
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameScope {
+    Service,
+    Global,
+}
+
+/// TypeScript type used for date and date-time strings
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DateType {
+    /// Keep the JSON wire type
+    #[default]
+    #[value(name = "string")]
+    String,
+    #[value(name = "Date")]
+    Date,
+}
+
+/// TypeScript type used for `format: int64` integers
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Int64Type {
+    /// Plain `number`, which loses precision above 2^53
+    #[default]
+    Number,
+    /// The decimal string many APIs send for 64-bit IDs
+    String,
+    Bigint,
+}
+
+/// Naming style of the generated service file names
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FileCase {
+    /// `UserController.ts`
+    #[default]
+    Pascal,
+    /// `user-controller.ts`
+    Kebab,
+    /// `userController.ts`
+    Camel,
+}
+
+/// HTTP clients requests can be sent with, and data-fetching libraries hooks can be generated for
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Client {
+    /// TanStack Query `useQuery` / `useMutation` hooks
+    ReactQuery,
+    /// `useSWR` hooks for GET operations
+    Swr,
+    /// `axiosInstance.get<T>(url, config)` calls on a shared axios instance
+    Axios,
+    /// Native `fetch`, with no runtime dependency
+    Fetch,
+    /// An `@Injectable` service class per tag whose methods return `HttpClient` observables
+    Angular,
+}
+
+impl Client {
+    /// Whether this is a data-fetching library wrapping the functions in hooks, rather
+    /// than the HTTP client the functions themselves use
+    pub fn is_hooks(self) -> bool {
+        matches!(self, Client::ReactQuery | Client::Swr)
+    }
+}
+
+impl Args {
+    /// Data-fetching library to generate hooks for
+    pub fn hooks_client(&self) -> Option<Client> {
+        self.client.iter().copied().find(|c| c.is_hooks())
+    }
+
+    /// HTTP client of the generated functions, `None` for the `request` wrapper
+    pub fn http_client(&self) -> Option<Client> {
+        self.client.iter().copied().find(|c| !c.is_hooks())
+    }
+}
+
+/// Third-party runtimes a compatible client can be generated for
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compat {
+    /// `client.ts` with an openapi-typescript style `paths` interface and `createClient<paths>()`
+    #[value(name = "openapi-fetch")]
+    OpenapiFetch,
+}
+
+/// Config file looked up in the working directory when `--config` is not given
+const DEFAULT_CONFIG_FILE: &str = ".ropenapirc.json";
+
+/// Options read from a config file, keyed by the long name of the CLI flag they set
+/// (`request_lib_path`, `request-lib-path` and `requestLibPath` are all accepted)
+#[derive(Deserialize, Debug, Default)]
+pub struct Config {
+    #[serde(flatten)]
+    options: BTreeMap<String, Value>,
+}
+
+impl Config {
+    fn load(path: &str) -> Result<Self> {
+        let content = std::fs
+            ::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid config file: {}", path))
+    }
+
+    /// Translate the options into command-line arguments, so they go through the same
+    /// parsing and validation as flags given on the command line. Repeatable options also
+    /// given on the command line `argv` are left out, since their values would add up.
+    fn to_args(&self, argv: &[String]) -> Result<Vec<String>> {
+        let command = Args::command();
+        let given = command.clone().ignore_errors(true).get_matches_from(argv);
+        let mut args = Vec::new();
+        for (key, value) in &self.options {
+            let long = to_flag_name(key);
+            let arg = command
+                .get_arguments()
+                .find(|a| a.get_long() == Some(long.as_str()) && long != "config")
+                .with_context(|| format!("Unknown option '{}' in config file", key))?;
+            let append = matches!(arg.get_action(), ArgAction::Append);
+            if append && given.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine) {
+                continue;
+            }
+            let flag = format!("--{}", long);
+            match value {
+                Value::Bool(true) => args.push(flag),
+                Value::Bool(false) | Value::Null => {}
+                Value::Array(items) => {
+                    let items: Vec<String> = items.iter().map(config_value).collect();
+                    if append {
+                        for item in items {
+                            args.push(flag.clone());
+                            args.push(item);
+                        }
+                    } else {
+                        args.push(flag);
+                        args.push(items.join(","));
+                    }
+                }
+                other => {
+                    args.push(flag);
+                    args.push(config_value(other));
+                }
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// Render a scalar config value as a command-line value
+fn config_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Turn a config key in snake_case, kebab-case or camelCase into a long flag name
+fn to_flag_name(key: &str) -> String {
+    let mut name = String::new();
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            name.push('-');
+            name.push(c.to_ascii_lowercase());
+        } else if c == '_' {
+            name.push('-');
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
+
+/// Find the value of `--config` in the raw command line
+fn config_path(argv: &[String]) -> Option<String> {
+    argv.iter()
+        .enumerate()
+        .find_map(|(idx, arg)| {
+            if arg == "--config" {
+                argv.get(idx + 1).cloned()
+            } else {
+                arg.strip_prefix("--config=").map(String::from)
+            }
+        })
+}
+
+/// Parse the command line, preceded by the options of the config file,
+/// so flags given on the command line take precedence over the config
+pub fn parse_args() -> Result<Args> {
+    let argv: Vec<String> = std::env::args().collect();
+    let config = match config_path(&argv) {
+        Some(path) => Some(Config::load(&path)?),
+        None if Path::new(DEFAULT_CONFIG_FILE).is_file() => Some(Config::load(DEFAULT_CONFIG_FILE)?),
+        None => None,
+    };
+
+    let mut merged: Vec<String> = argv.iter().take(1).cloned().collect();
+    if let Some(config) = config {
+        merged.extend(config.to_args(&argv)?);
+    }
+    merged.extend(argv.iter().skip(1).cloned());
+    let args = Args::parse_from(merged);
+    if let Some(message) = conflict(&args) {
+        Args::command().error(clap::error::ErrorKind::ArgumentConflict, message).exit();
+    }
+    Ok(args)
+}
+
+/// Describe options that cannot be generated together. Checked after parsing, as they
+/// depend on the values given to `--client`.
+fn conflict(args: &Args) -> Option<String> {
+    for (kind, is_hooks) in [("data-fetching library", true), ("HTTP client", false)] {
+        let clients: Vec<Client> = args.client.iter().copied().filter(|c| c.is_hooks() == is_hooks).collect();
+        if clients.len() > 1 {
+            let names: Vec<String> = clients
+                .iter()
+                .filter_map(|c| c.to_possible_value().map(|v| v.get_name().to_string()))
+                .collect();
+            return Some(format!("--client takes at most one {}, got {}", kind, names.join(", ")));
+        }
+    }
+    // Angular services are classes, so there are no exported functions to wrap, poll or template
+    if args.http_client() == Some(Client::Angular) {
+        if args.hooks_client().is_some() || args.polling {
+            return Some("--client angular cannot be combined with hooks or --polling".to_string());
+        }
+        if args.template.is_some() {
+            return Some("--template cannot be combined with --client angular".to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(options: Value) -> Config {
+        serde_json::from_value(options).unwrap()
+    }
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        ["ropenapi"].iter().chain(args).map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn config_options_become_flags() {
+        let config = config(json!({ "swagger": ["a.json", "b.json"], "argsStyle": "object", "zod": true }));
+        assert_eq!(
+            config.to_args(&argv(&[])).unwrap(),
+            ["--args-style", "object", "--swagger", "a.json", "--swagger", "b.json", "--zod"]
+        );
+    }
+
+    #[test]
+    fn repeatable_options_on_the_command_line_replace_the_config() {
+        let config = config(json!({ "swagger": ["a.json"], "header": ["X-Token: 1"] }));
+        assert_eq!(config.to_args(&argv(&["-s", "c.json"])).unwrap(), ["--header", "X-Token: 1"]);
+        assert_eq!(config.to_args(&argv(&["--swagger=c.json", "--header", "X-Token: 2"])).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn angular_rejects_function_options() {
+        let template = std::env::temp_dir().join(format!("ropenapi-template-{}", std::process::id()));
+        std::fs::write(&template, "export const {{name}} = {{body}};").unwrap();
+        let template = template.to_string_lossy().into_owned();
+        let conflict = |flags: &[&str]| conflict(&Args::parse_from(argv(&[&["-s", "api.json"], flags].concat())));
+        let hooks_or_polling = Some("--client angular cannot be combined with hooks or --polling".to_string());
+
+        assert_eq!(conflict(&["--client", "angular", "--polling"]), hooks_or_polling);
+        assert_eq!(conflict(&["--client", "angular,react-query"]), hooks_or_polling);
+        assert_eq!(
+            conflict(&["--client", "angular", "--template", &template]),
+            Some("--template cannot be combined with --client angular".to_string())
+        );
+        assert_eq!(conflict(&["--template", &template]), None);
+        std::fs::remove_file(&template).unwrap();
+    }
 }
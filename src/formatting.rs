@@ -0,0 +1,94 @@
+/// Indentation unit used by all emitters; `normalize` converts it to the `--indent` width
+pub const INDENT: &str = "  ";
+
+/// Indent every non-empty line of `text` by `level` indentation units.
+/// Blank lines are kept empty so nested blocks never carry trailing whitespace.
+pub fn indent(text: &str, level: usize) -> String {
+    let prefix = INDENT.repeat(level);
+    text.lines()
+        .map(|line| {
+            if line.trim().is_empty() { String::new() } else { format!("{}{}", prefix, line) }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Strip the common leading whitespace of all non-empty lines,
+/// so an over-indented block can be re-indented to its enclosing level.
+pub fn dedent(text: &str) -> String {
+    let common = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    text.lines()
+        .map(|line| if line.trim().is_empty() { "" } else { &line[common..] })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Render a brace-delimited block whose members are aligned one level deeper
+/// than the braces, e.g. object types and request config literals.
+pub fn block(members: &[String]) -> String {
+    if members.is_empty() {
+        return String::from("{}");
+    }
+    let body = members
+        .iter()
+        .map(|m| indent(&dedent(m), 1))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("{{\n{}\n}}", body)
+}
+
+/// Render a JSDoc comment from the given lines, or an empty string when there is nothing to say.
+/// `*/` inside the text is escaped so spec descriptions cannot close the comment early.
+pub fn jsdoc(lines: &[String]) -> String {
+    let text = lines.join("\n");
+    let lines: Vec<String> = text
+        .lines()
+        .map(|l| l.trim_end().replace("*/", "*\\/"))
+        .collect();
+    if lines.iter().all(|l| l.trim().is_empty()) {
+        return String::new();
+    }
+    let mut out = String::from("/**\n");
+    for line in lines {
+        if line.is_empty() {
+            out.push_str(" *\n");
+        } else {
+            out.push_str(&format!(" * {}\n", line));
+        }
+    }
+    out.push_str(" */");
+    out
+}
+
+/// Final pass over a generated file: re-indent from the `INDENT` unit to `width` spaces,
+/// trim trailing whitespace, collapse runs of blank lines and end with a single newline
+pub fn normalize(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut pending_blank = false;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            pending_blank = !out.is_empty();
+            continue;
+        }
+        if pending_blank {
+            out.push('\n');
+            pending_blank = false;
+        }
+        let content = line.trim_start_matches(' ');
+        let leading = line.len() - content.len();
+        // Keep the odd space of JSDoc continuation lines (` * ...`) after the indentation
+        let levels = leading / INDENT.len();
+        let rest = leading % INDENT.len();
+        out.push_str(&" ".repeat(levels * width + rest));
+        out.push_str(content);
+        out.push('\n');
+    }
+    out
+}
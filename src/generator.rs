@@ -1,156 +1,880 @@
-use anyhow::{ Context, Result };
-use std::fs::{ self, OpenOptions };
-use std::io::Write;
-use std::path::Path;
+mod diff;
+mod guards;
+mod hooks;
+mod mock;
+mod openapi_fetch;
+mod search_params;
+mod transport;
+mod zod;
 
-use crate::models::Service;
-use crate::cli::Args;
+use anyhow::{ bail, Context, Result };
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::sync::Mutex;
+use std::thread;
+
+use crate::formatting::{ block, jsdoc, normalize };
+use crate::models::{ ApiOperation, BodyEncoding, FunctionOptions, RenderOptions, Service };
+use crate::naming::{ capitalize_first, to_kebab_case };
+use crate::cli::{ Args, Compat, FileCase };
 use crate::models::TypeDefinition;
+use crate::template;
+
+/// Destination of the generated files
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Sink {
+    /// Write the files to disk
+    Files,
+    /// `--dry-run`: describe the files that would be written
+    DryRun,
+    /// `--stdout`: print the files to standard output, keeping it free of log lines
+    Stdout,
+}
+
+impl Sink {
+    fn from_args(args: &Args) -> Self {
+        if args.dry_run {
+            Sink::DryRun
+        } else if args.stdout {
+            Sink::Stdout
+        } else {
+            Sink::Files
+        }
+    }
+}
 
-/// Write all services to disk
+/// A generated service file and the names it exports, kept for the `--index` barrel
+struct ServiceFile {
+    path: PathBuf,
+    exports: Vec<(String, bool)>,
+}
+
+/// Write all services to disk, or with `--dry-run`, only describe the files that would be written
 pub fn write_services(out_dir: &Path, services: &[Service], args: &Args) -> Result<()> {
+    let sink = Sink::from_args(args);
     let services_dir = out_dir.join(args.project_name.as_ref().unwrap_or(&String::from("")));
-    ensure_dir(&services_dir)?;
+    if sink == Sink::Files {
+        if args.clean {
+            clean_dir(&services_dir)?;
+        }
+        ensure_dir(&services_dir)?;
+    }
 
-    for service in services {
-        write_service(
-            &services_dir,
-            service,
-            args.request_lib_path
-                .as_ref()
-                .unwrap_or(&String::from("import request from \'@/services/http\';")),
-            args.api_prefix.as_ref().unwrap_or(&String::from(""))
-        )?;
+    // Dry runs and stdout print as they go, so keep them sequential for a stable order
+    let written = if args.parallel && sink == Sink::Files {
+        write_services_parallel(&services_dir, services, args)
+    } else {
+        services
+            .iter()
+            .map(|service| write_service(&services_dir, service, args))
+            .collect()
+    };
+    let written = written?;
+
+    // Report in service order, whatever order the files were written in
+    if sink == Sink::Files {
+        for (service, file) in services.iter().zip(&written) {
+            println!("  ✓ Generated {}/{}", service.name, file.path.file_name().unwrap().to_string_lossy());
+        }
+    }
+    let mut extra_files: Vec<(String, String)> = Vec::new();
+    let mut modules: Vec<(String, Vec<(String, bool)>)> = services
+        .iter()
+        .zip(written)
+        .map(|(service, file)| (service_file_stem(service, args.file_case), file.exports))
+        .collect();
+    if args.shared_types {
+        let content = render_shared_types(services, args);
+        modules.push((SHARED_TYPES_MODULE.to_string(), owned_exported_names(&content)));
+        extra_files.push((format!("{}.ts", SHARED_TYPES_MODULE), content));
+    }
+    if args.index {
+        extra_files.push(("index.ts".to_string(), render_index(modules)));
+    }
+    if args.emit_barrel_types {
+        extra_files.push(("types.ts".to_string(), render_types_barrel(services, args.namespace.as_deref(), args.file_case)));
+    }
+    if args.compat == Some(Compat::OpenapiFetch) {
+        // The client has a single base URL, so it takes the base path of the first operation
+        let api_prefix = args.api_prefix
+            .as_deref()
+            .or_else(|| {
+                services
+                    .iter()
+                    .flat_map(|s| &s.operations)
+                    .next()
+                    .and_then(|op| op.base_path.as_deref())
+            })
+            .unwrap_or("");
+        extra_files.push((
+            "client.ts".to_string(),
+            openapi_fetch::render_client(services, api_prefix, args.namespace.as_deref(), args.shared_types, args.file_case),
+        ));
+    }
+
+    for (file_name, content) in extra_files {
+        emit_file(&services_dir.join(&file_name), &content, None, args)?;
+        if sink == Sink::Files {
+            println!("  ✓ Generated {}", file_name);
+        }
+    }
+
+    if args.manifest {
+        emit_file(&out_dir.join("manifest.json"), &render_manifest(services, args)?, None, args)?;
+        if sink == Sink::Files {
+            println!("  ✓ Generated manifest.json");
+        }
     }
 
     Ok(())
 }
 
-/// Write a single service (both api file and types file)
-fn write_service(
-    services_root: &Path,
-    service: &Service,
-    request_lib: &str,
-    api_prefix: &str
-) -> Result<()> {
-    let service_name = if service.name.to_lowercase().ends_with("Controller") {
-        service.name.clone()
-    } else {
-        format!("{}Controller", capitalize(&service.name))
+/// `manifest.json` written with `--manifest`, summarizing the generated API surface
+#[derive(Serialize)]
+struct Manifest {
+    services: Vec<ManifestService>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestService {
+    name: String,
+    /// Path of the service file relative to the output directory
+    file: String,
+    operation_count: usize,
+    /// Names of the types declared for the service
+    types: Vec<String>,
+}
+
+/// Render the manifest of every service, in service order
+fn render_manifest(services: &[Service], args: &Args) -> Result<String> {
+    let project_dir = Path::new(args.project_name.as_deref().unwrap_or(""));
+    let manifest = Manifest {
+        services: services
+            .iter()
+            .map(|service| ManifestService {
+                name: service.name.clone(),
+                file: project_dir
+                    .join(format!("{}.ts", service_file_stem(service, args.file_case)))
+                    .to_string_lossy()
+                    .replace('\\', "/"),
+                operation_count: service.operations.len(),
+                types: service.type_definitions.keys().cloned().collect(),
+            })
+            .collect(),
     };
+    let json = serde_json::to_string_pretty(&manifest).context("Failed to serialize the manifest")?;
+    Ok(format!("{}\n", json))
+}
 
-    let file_path = services_root.join(format!("{}.ts", service_name));
+/// Write a generated file, print it with `--stdout`, or with `--dry-run`, print its path with
+/// an optional summary and a diff against the current file on disk
+fn emit_file(path: &Path, content: &str, summary: Option<String>, args: &Args) -> Result<()> {
+    let content = normalize(content, usize::from(args.indent));
+    let content = content.as_str();
+    match Sink::from_args(args) {
+        Sink::Files => {
+            fs::write(path, content).with_context(|| format!("Failed to write file: {}", path.display()))?;
+        }
+        Sink::Stdout => {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            print!("// File: {}\n\n{}", name, content);
+        }
+        Sink::DryRun => {
+            let summary = summary.map(|s| format!(" ({})", s)).unwrap_or_default();
+            match fs::read_to_string(path) {
+                Ok(existing) if existing == content => {
+                    println!("  = {}{} unchanged", path.display(), summary);
+                }
+                Ok(existing) => {
+                    println!("  ~ {}{} would change", path.display(), summary);
+                    print!("{}", diff::unified_diff(&existing, content, &path.display().to_string()));
+                }
+                Err(_) => println!("  + {}{} would be created", path.display(), summary),
+            }
+        }
+    }
+    Ok(())
+}
 
-    write_service_to_file(&file_path, service, request_lib, api_prefix)?;
+/// Render an `index.ts` barrel re-exporting every module, given with the names it exports,
+/// sorted by file name. A name exported by several modules, such as `routes` or a type
+/// declared by two services, is ambiguous for `export *`, so it is also re-exported
+/// explicitly from the first module declaring it.
+fn render_index(mut modules: Vec<(String, Vec<(String, bool)>)>) -> String {
+    modules.sort_by(|a, b| a.0.cmp(&b.0));
+    modules.dedup_by(|a, b| a.0 == b.0);
 
-    println!("  ✓ Generated {}/{}", service.name, file_path.file_name().unwrap().to_string_lossy());
+    let mut lines: Vec<String> = modules
+        .iter()
+        .map(|(module, _)| format!("export * from './{}';\n", module))
+        .collect();
 
-    Ok(())
+    // Module and kind of the first export of each name, and the names exported again
+    let mut first: BTreeMap<&str, (&str, bool)> = BTreeMap::new();
+    let mut repeated: Vec<&str> = Vec::new();
+    for (module, exports) in &modules {
+        for (name, is_type) in exports {
+            let (name, is_type) = (name.as_str(), *is_type);
+            if first.contains_key(name) {
+                if !repeated.contains(&name) {
+                    repeated.push(name);
+                }
+            } else {
+                first.insert(name, (module, is_type));
+            }
+        }
+    }
+    for (module, _) in &modules {
+        for is_type in [false, true] {
+            let names: Vec<&str> = repeated
+                .iter()
+                .copied()
+                .filter(|name| first[name] == (module.as_str(), is_type))
+                .collect();
+            if !names.is_empty() {
+                lines.push(
+                    format!(
+                        "export {}{{ {} }} from './{}';\n",
+                        if is_type { "type " } else { "" },
+                        names.join(", "),
+                        module
+                    )
+                );
+            }
+        }
+    }
+    lines.concat()
 }
 
-fn capitalize(s: &str) -> String {
-    let mut chars = s.chars();
-    match chars.next() {
-        None => String::new(),
-        Some(f) => f.to_uppercase().collect::<String>() + chars.as_str(),
+/// Names declared with a top-level `export` in a generated module, each with whether it is
+/// only a type: a type alias, an interface, or a namespace holding nothing but types
+fn exported_names(content: &str) -> Vec<(&str, bool)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut names = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let Some(declaration) = line.strip_prefix("export ") else {
+            continue;
+        };
+        let declaration = declaration.trim_start_matches("declare ").trim_start_matches("async ");
+        let Some((keyword, rest)) = declaration.split_once(' ') else {
+            continue;
+        };
+        let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$')).unwrap_or(rest.len());
+        let name = &rest[..end];
+        if name.is_empty() {
+            continue;
+        }
+        let is_type = match keyword {
+            "type" | "interface" => true,
+            "namespace" => {
+                // Members are indented by one level until the closing brace
+                !lines[idx + 1..]
+                    .iter()
+                    .take_while(|member| **member != "}")
+                    .any(|member| {
+                        ["const ", "let ", "function ", "class ", "enum "]
+                            .iter()
+                            .any(|kind| member.starts_with(&format!("  export {}", kind)))
+                    })
+            }
+            "const" | "let" | "function" | "class" | "enum" | "abstract" => false,
+            _ => continue,
+        };
+        names.push((name, is_type));
     }
+    names
 }
 
-fn write_service_to_file(
-    path: &Path,
-    service: &Service,
-    request_lib: &str,
-    api_prefix: &str
-) -> Result<()> {
-    write_api_file_with_request_lib(path, service, request_lib, api_prefix)?;
-    write_types_file(path, service)?;
+/// Owned `exported_names` of a module, kept after its content is written
+fn owned_exported_names(content: &str) -> Vec<(String, bool)> {
+    exported_names(content)
+        .into_iter()
+        .map(|(name, is_type)| (name.to_string(), is_type))
+        .collect()
+}
 
-    Ok(())
+/// Render a `types.ts` barrel re-exporting only the type declarations of every service,
+/// each name from the first service declaring it. With `namespace`, each service's
+/// namespace is re-exported under a `XxxControllerTypes` alias instead.
+fn render_types_barrel(services: &[Service], namespace: Option<&str>, file_case: FileCase) -> String {
+    let mut exported: Vec<&str> = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+    for service in services {
+        let mut names: Vec<&str> = service.type_definitions.keys().map(String::as_str).collect();
+        names.extend(placeholder_types(service));
+        if names.is_empty() {
+            continue;
+        }
+        let module = service_module_name(service);
+        let exports = match namespace {
+            Some(namespace) => format!("{} as {}Types", namespace, module),
+            None => {
+                names.retain(|name| !exported.contains(name));
+                if names.is_empty() {
+                    continue;
+                }
+                exported.extend(&names);
+                names.join(", ")
+            }
+        };
+        lines.push(format!("export type {{ {} }} from './{}';\n", exports, service_file_stem(service, file_case)));
+    }
+    lines.concat()
+}
+
+/// Write services on a pool of `--jobs` worker threads (default: number of CPUs).
+/// Results are collected per service index so the returned paths keep the input order.
+fn write_services_parallel(
+    services_dir: &Path,
+    services: &[Service],
+    args: &Args
+) -> Result<Vec<ServiceFile>> {
+    let jobs = args.jobs
+        .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .clamp(1, services.len().max(1));
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<ServiceFile>>>> = Mutex::new(
+        (0..services.len()).map(|_| None).collect()
+    );
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(service) = services.get(idx) else {
+                        break;
+                    };
+                    let result = write_service(services_dir, service, args);
+                    results.lock().unwrap()[idx] = Some(result);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every service is written by a worker"))
+        .collect()
 }
 
-/// Write API service file
-fn write_api_file_with_request_lib(
-    path: &Path,
-    service: &Service,
-    request_lib: &str,
-    api_prefix: &str
-) -> Result<()> {
+/// Write a single service (both api file and types file), returning the written file path
+/// and its exports
+fn write_service(services_root: &Path, service: &Service, args: &Args) -> Result<ServiceFile> {
+    let path = services_root.join(format!("{}.ts", service_file_stem(service, args.file_case)));
+
+    let exports = write_service_to_file(&path, service, args)?;
+
+    Ok(ServiceFile { path, exports })
+}
+
+/// Module name of a service file, without the `.ts` extension
+fn service_module_name(service: &Service) -> String {
+    if service.name.to_lowercase().ends_with("controller") {
+        capitalize_first(&service.name)
+    } else {
+        format!("{}Controller", capitalize_first(&service.name))
+    }
+}
+
+/// File name of a service module, without the `.ts` extension, in the `--file-case` style.
+/// Identifiers derived from the module (e.g. `UserControllerTypes`) keep the PascalCase name.
+fn service_file_stem(service: &Service, file_case: FileCase) -> String {
+    let module = service_module_name(service);
+    match file_case {
+        FileCase::Pascal => module,
+        FileCase::Kebab => to_kebab_case(&module),
+        FileCase::Camel => {
+            let mut chars = module.chars();
+            chars.next().map(|c| c.to_lowercase().chain(chars).collect()).unwrap_or_default()
+        }
+    }
+}
+
+/// Write the content of a service file, replacing any previous output, and return the
+/// names it exports
+fn write_service_to_file(path: &Path, service: &Service, args: &Args) -> Result<Vec<(String, bool)>> {
+    let content = render_service(service, args)?;
+
+    let summary = format!(
+        "{} operations, {} types",
+        service.operations.len(),
+        service.type_definitions.len()
+    );
+    emit_file(path, &content, Some(summary), args)?;
+    Ok(owned_exported_names(&content))
+}
+
+/// Render the API functions followed by the type definitions of a service. With
+/// `--shared-types` the definitions live in the shared module instead.
+fn render_service(service: &Service, args: &Args) -> Result<String> {
+    let mut content = render_api_with_request_lib(service, args)?;
+    if !args.shared_types {
+        content.push_str(&render_types(service, args));
+    }
+    Ok(content)
+}
+
+/// Render the API functions of a service
+fn render_api_with_request_lib(service: &Service, args: &Args) -> Result<String> {
+    let client = args.http_client();
+    let request_lib = args.request_lib_path.as_deref().or_else(|| transport::default_import(client));
+    // Clients without a params option (fetch) always serialize the query into the URL
+    let query_string = args.query_string || transport::query_in_url(client);
+    // With --namespace the types live inside the namespace, so operations refer to them qualified
+    let namespaced;
+    let (service, original) = match args.namespace.as_deref() {
+        Some(namespace) => {
+            namespaced = namespace_service(service, namespace);
+            (&namespaced, service)
+        }
+        None => (service, service),
+    };
+
     let mut content = String::new();
 
     // Add header with imports
-    content.push_str("// @ts-expect-error\n");
-    content.push_str(request_lib);
-    content.push_str("\n\n");
-    // content.push_str("import * as Types from './types';\n");
-    // content.push_str("import type { IResponse } from '@/types';\n\n");
+    if let Some(request_lib) = request_lib {
+        content.push_str("// @ts-expect-error\n");
+        content.push_str(request_lib);
+        content.push('\n');
+    }
+    if let Some(imports) = transport::imports(client) {
+        content.push_str(imports);
+        content.push('\n');
+    }
+    if let Some(client) = args.hooks_client() {
+        content.push_str(hooks::imports(client));
+        content.push('\n');
+    }
+    if let Some(wrapper) = &args.response_wrapper {
+        // The wrapper usually lives next to the request function
+        let module = args.response_wrapper_from.as_deref().or_else(|| request_lib.and_then(import_source));
+        if let Some(module) = module {
+            content.push_str(&format!("import type {{ {} }} from '{}';\n", wrapper, module));
+        }
+    }
+    if args.zod && !args.shared_types {
+        content.push_str(zod::IMPORT);
+        content.push('\n');
+    }
+    // Type definitions are rendered into the same module below the functions,
+    // so the referenced types resolve without an import unless they are shared
+    if args.shared_types {
+        let names = imported_type_names(original);
+        let imports = match args.namespace.as_deref() {
+            Some(namespace) if !names.is_empty() => Some(namespace.to_string()),
+            Some(_) => None,
+            None if !names.is_empty() => Some(names.join(", ")),
+            None => None,
+        };
+        if let Some(imports) = imports {
+            content.push_str(&format!("import type {{ {} }} from './{}';\n", imports, SHARED_TYPES_MODULE));
+        }
+    }
+    content.push('\n');
+
+    if query_string && service.operations.iter().any(|op| op.query_type.is_some()) {
+        content.push_str(QUERY_STRING_HELPER);
+        content.push_str("\n\n");
+    }
+    let sends = |encoding| service.operations.iter().any(|op| op.body_type.is_some() && op.body_encoding == encoding);
+    if sends(BodyEncoding::FormUrlEncoded) {
+        content.push_str(URL_ENCODED_HELPER);
+        content.push_str("\n\n");
+    }
+    if sends(BodyEncoding::Multipart) {
+        content.push_str(FORM_DATA_HELPER);
+        content.push_str("\n\n");
+    }
+
+    let operations: Vec<(&ApiOperation, FunctionOptions)> = service.operations
+        .iter()
+        .map(|operation| {
+            // --api-prefix wins over the base path declared by the spec
+            let api_prefix = args.api_prefix
+                .as_deref()
+                .or(operation.base_path.as_deref())
+                .unwrap_or("");
+            let options = FunctionOptions {
+                path_prefix: api_prefix,
+                args_style: args.args_style,
+                query_string,
+                response_wrapper: args.response_wrapper.as_deref(),
+                auth_token: Some(args.auth_token.as_str()).filter(|_| args.auth),
+                template: args.template.as_deref().unwrap_or(template::FUNCTION_TEMPLATE),
+                source_comment: args.source_comments,
+                abort_signal: args.abort_signal,
+            };
+            (operation, options)
+        })
+        .collect();
+    content.push_str(&transport::render_functions(service, client, &operations));
+
+    if args.polling && service.operations.iter().any(|op| op.long_running) {
+        content.push_str("\n\n");
+        content.push_str(POLL_OPTIONS_TYPE);
+        for operation in service.operations.iter().filter(|op| op.long_running) {
+            content.push_str("\n\n");
+            content.push_str(
+                &operation.to_polling_helper(args.args_style, &|url| transport::status_request(client, url))?
+            );
+        }
+    }
+
+    if args.fixtures {
+        for fixture in service.operations.iter().filter_map(|op| op.to_fixture()) {
+            content.push_str("\n\n");
+            content.push_str(&fixture);
+        }
+    }
+
+    if args.search_parsers {
+        if let Some(parsers) = search_params::render_search_param_parsers(original, args.namespace.as_deref()) {
+            content.push_str("\n\n");
+            content.push_str(&parsers);
+        }
+    }
+
+    if args.routes {
+        content.push_str("\n\n");
+        content.push_str(&render_routes(service));
+    }
 
-    // Add operations with proper spacing
-    for (idx, operation) in service.operations.iter().enumerate() {
-        content.push_str(&operation.to_typescript_function(api_prefix));
-        if idx < service.operations.len() - 1 {
+    if let Some(client) = args.hooks_client() {
+        if let Some(hooks) = hooks::render_hooks(service, client, args.args_style) {
             content.push_str("\n\n");
+            content.push_str(&hooks);
         }
     }
 
     content.push('\n');
+    Ok(content)
+}
 
-    fs
-        ::write(path, &content)
-        .with_context(|| format!("Failed to write API file: {}", path.display()))?;
+/// Options accepted by the generated `pollXxx` helpers of long-running operations
+const POLL_OPTIONS_TYPE: &str = "export type PollOptions<T> = {
+  /** Status endpoint to poll, derived from the initial response */
+  statusUrl: (initial: any) => string;
+  /** Whether the polled status represents a finished operation */
+  isDone: (status: any) => boolean;
+  /** Extract the final result from the finished status, defaults to the status itself */
+  result?: (status: any) => T;
+  intervalMs?: number;
+  maxAttempts?: number;
+};";
 
-    Ok(())
+/// Module an import statement imports from, e.g. `@/services/http` for
+/// `import request from '@/services/http';`
+fn import_source(statement: &str) -> Option<&str> {
+    let (_, rest) = statement.rsplit_once(" from ")?;
+    let rest = rest.trim().trim_end_matches(';');
+    rest.strip_prefix('\'').and_then(|r| r.strip_suffix('\''))
+        .or_else(|| rest.strip_prefix('"').and_then(|r| r.strip_suffix('"')))
 }
 
-/// Write types definition file
-fn write_types_file(path: &Path, service: &Service) -> Result<()> {
-    let mut content = String::new();
+/// Serializer used by `--query-string`: skips `undefined`/`null` values and repeats the key
+/// for every item of an array
+const QUERY_STRING_HELPER: &str = "const toQueryString = (params?: object): string => {
+  const search = new URLSearchParams();
+  Object.entries(params ?? {}).forEach(([key, value]) => {
+    if (value === undefined || value === null) return;
+    (Array.isArray(value) ? value : [value]).forEach((item) => search.append(key, String(item)));
+  });
+  const query = search.toString();
+  return query ? `?${query}` : '';
+};";
 
-    content.push_str("/**\n");
-    content.push_str(&format!(" * Type definitions for {} service\n", service.name));
-    content.push_str(" */\n\n");
+/// Converter of urlencoded bodies, serializing values like `--query-string`
+const URL_ENCODED_HELPER: &str = "const toUrlEncoded = (data?: object): URLSearchParams => {
+  const search = new URLSearchParams();
+  Object.entries(data ?? {}).forEach(([key, value]) => {
+    if (value === undefined || value === null) return;
+    (Array.isArray(value) ? value : [value]).forEach((item) => search.append(key, String(item)));
+  });
+  return search;
+};";
 
-    let type_defs = service.type_definitions.values().collect::<Vec<_>>();
+/// Converter of multipart bodies: files are appended as is, objects as JSON, and every item
+/// of an array under the same key
+const FORM_DATA_HELPER: &str = "const toFormData = (data?: object): FormData => {
+  const form = new FormData();
+  Object.entries(data ?? {}).forEach(([key, value]) => {
+    if (value === undefined || value === null) return;
+    (Array.isArray(value) ? value : [value]).forEach((item) => {
+      form.append(key, item instanceof Blob ? item : typeof item === 'object' ? JSON.stringify(item) : String(item));
+    });
+  });
+  return form;
+};";
 
-    for type_def in type_defs.clone() {
-        content.push_str(&type_def.to_typescript());
-        content.push_str("\n\n");
+/// Render a per-service `routes` map from function name to the spec path of each operation
+fn render_routes(service: &Service) -> String {
+    let members: Vec<String> = service.operations
+        .iter()
+        .map(|op| format!("{}: '{}',", op.function_name, op.path))
+        .collect();
+    format!("export const routes = {} as const;", block(&members))
+}
+
+/// Module holding every type definition with `--shared-types`
+const SHARED_TYPES_MODULE: &str = "types";
+
+/// Render the type definitions of a service
+fn render_types(service: &Service, args: &Args) -> String {
+    render_declarations(
+        &format!("Type definitions for {} service", service.name),
+        service.type_definitions.values(),
+        &placeholder_types(service),
+        args
+    )
+}
+
+/// Render the shared types module: the definitions of all services deduplicated by name
+/// (the first service declaring a name wins), plus the placeholders any of them needs
+fn render_shared_types(services: &[Service], args: &Args) -> String {
+    let mut type_defs: BTreeMap<&str, &TypeDefinition> = BTreeMap::new();
+    for service in services {
+        for (name, type_def) in &service.type_definitions {
+            type_defs.entry(name).or_insert(type_def);
+        }
+    }
+    let mut placeholders: Vec<&str> = Vec::new();
+    for name in services.iter().flat_map(placeholder_types) {
+        if !type_defs.contains_key(name) && !placeholders.contains(&name) {
+            placeholders.push(name);
+        }
     }
 
-    // Add placeholder types for operations if not in definitions
-    for operation in &service.operations {
-        if
-            !type_already_exists(type_defs.clone(), &operation.request_type) &&
-            operation.request_type != "any"
-        {
-            content.push_str(&format!("export type {} = any;\n\n", operation.request_type));
+    let declarations = render_declarations(
+        "Type definitions shared by all services",
+        type_defs.into_values(),
+        &placeholders,
+        args
+    );
+    if args.zod {
+        format!("{}\n\n{}", zod::IMPORT, declarations)
+    } else {
+        declarations
+    }
+}
+
+/// Render a block of type declarations under a JSDoc header, wrapped in `--namespace` if set
+fn render_declarations<'a>(
+    header: &str,
+    type_defs: impl Iterator<Item = &'a TypeDefinition>,
+    placeholders: &[&str],
+    args: &Args
+) -> String {
+    let options = RenderOptions {
+        enum_style: args.enum_style,
+        enum_consts: args.enum_consts,
+        type_style: args.type_style,
+        empty_object: args.empty_object,
+    };
+
+    let mut content = String::new();
+
+    content.push_str(&jsdoc(&[header.to_string()]));
+    content.push_str("\n\n");
+
+    let type_defs: Vec<&TypeDefinition> = type_defs.collect();
+    let mut declarations: Vec<String> = if args.zod {
+        let declared = |name: &str| {
+            type_defs.iter().any(|type_def| type_def.name == name) || placeholders.contains(&name)
+        };
+        let recursive = zod::recursive_types(&type_defs);
+        type_defs
+            .iter()
+            .map(|type_def| {
+                let type_declaration = recursive
+                    .contains(type_def.name.as_str())
+                    .then(|| type_def.to_typescript(&options));
+                zod::render_schema(type_def, &declared, type_declaration.as_deref())
+            })
+            .chain(placeholders.iter().map(|name| zod::render_placeholder(name)))
+            .collect()
+    } else {
+        type_defs
+            .iter()
+            .map(|type_def| type_def.to_typescript(&options))
+            .chain(placeholders.iter().map(|name| format!("export type {} = any;", name)))
+            .collect()
+    };
+
+    if args.mock {
+        let mock_options = mock::MockOptions {
+            type_defs: &type_defs,
+            include_optional: args.mock_optional,
+        };
+        for type_def in &type_defs {
+            let named_members = !args.zod && type_def.has_named_members(&options);
+            declarations.push(mock::render_mock(type_def, named_members, &mock_options));
         }
-        if
-            !type_already_exists(type_defs.clone(), &operation.response_type) &&
-            operation.response_type != "any"
-        {
-            content.push_str(&format!("export type {} = any;\n\n", operation.response_type));
+    }
+
+    if args.type_guards {
+        let has_guard = |name: &str| {
+            type_defs.iter().any(|type_def| type_def.name == name && guards::is_guarded(type_def))
+        };
+        declarations.extend(type_defs.iter().filter_map(|type_def| guards::render_guard(type_def, &has_guard)));
+    }
+
+    match args.namespace.as_deref() {
+        Some(namespace) if !declarations.is_empty() => {
+            content.push_str(
+                &format!("export namespace {} {}", namespace, block(&[declarations.join("\n\n")]))
+            );
         }
+        _ => content.push_str(&declarations.join("\n\n")),
     }
 
     // Trim trailing whitespace
-    let content = content.trim_end().to_string() + "\n";
+    content.trim_end().to_string() + "\n"
+}
 
-    if path.exists() {
-        OpenOptions::new().append(true).open(path)?.write_all(content.as_bytes())?;
-    } else {
-        fs
-            ::write(path, &content)
-            .with_context(|| format!("Failed to write types file: {}", path.display()))?;
+/// Copy of the service whose operations refer to its declared types as `Namespace.Type`
+fn namespace_service(service: &Service, namespace: &str) -> Service {
+    let placeholders = placeholder_types(service);
+    let qualify = |type_expr: &str| {
+        qualify_types(type_expr, |name| {
+            service.type_definitions.contains_key(name) || placeholders.contains(&name)
+        }, namespace)
+    };
+
+    let mut namespaced = service.clone();
+    for operation in namespaced.operations.iter_mut() {
+        operation.body_type = operation.body_type.as_deref().map(qualify);
+        operation.query_type = operation.query_type.as_deref().map(qualify);
+        operation.response_type = qualify(&operation.response_type);
+        operation.error_type = operation.error_type.as_deref().map(qualify);
+        for param in operation.path_params.iter_mut() {
+            param.field_type = qualify(&param.field_type);
+        }
     }
+    namespaced
+}
 
-    Ok(())
+/// Prefix the identifiers of a type expression accepted by `is_declared` with `qualifier.`,
+/// leaving string literals untouched, e.g. `{ a: User[] }` -> `{ a: Api.User[] }`
+fn qualify_types(type_expr: &str, is_declared: impl Fn(&str) -> bool, qualifier: &str) -> String {
+    let mut out = String::new();
+    let mut token = String::new();
+    let mut in_string = false;
+    let mut prev = '\0';
+    let flush = |token: &mut String, out: &mut String| {
+        if !token.is_empty() && is_declared(token) {
+            out.push_str(qualifier);
+            out.push('.');
+        }
+        out.push_str(token);
+        token.clear();
+    };
+    for c in type_expr.chars() {
+        if in_string {
+            out.push(c);
+            in_string = !(c == '\'' && prev != '\\');
+        } else if c.is_ascii_alphanumeric() || c == '_' || c == '$' {
+            token.push(c);
+        } else {
+            flush(&mut token, &mut out);
+            out.push(c);
+            in_string = c == '\'';
+        }
+        prev = c;
+    }
+    flush(&mut token, &mut out);
+    out
+}
+
+/// Declared types (definitions or placeholders) the operations of a service refer to,
+/// i.e. the names another module has to import to use its operations' types
+fn imported_type_names(service: &Service) -> Vec<&str> {
+    let placeholders = placeholder_types(service);
+    let mut names: Vec<&str> = Vec::new();
+    for operation in &service.operations {
+        for name in operation.referenced_types().into_iter().flat_map(type_identifiers) {
+            let declared = service.type_definitions.contains_key(name) || placeholders.contains(&name);
+            if declared && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+/// Identifier tokens of a type expression, e.g. `User` and `Role` in `{ user: User; roles: Role[] }`
+fn type_identifiers(type_expr: &str) -> impl Iterator<Item = &str> {
+    type_expr
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+        .filter(|token| token.chars().next().is_some_and(|c| !c.is_ascii_digit()))
+}
+
+/// Named types referenced by operations without a definition, which get an `any` placeholder
+fn placeholder_types(service: &Service) -> Vec<&str> {
+    let type_defs = service.type_definitions.values().collect::<Vec<_>>();
+    let mut placeholders: Vec<&str> = Vec::new();
+    for operation in &service.operations {
+        for type_name in operation.referenced_types() {
+            if
+                type_name != "any" &&
+                is_type_identifier(type_name) &&
+                !type_already_exists(type_defs.clone(), type_name) &&
+                !placeholders.contains(&type_name)
+            {
+                placeholders.push(type_name);
+            }
+        }
+    }
+    placeholders
 }
 
 /// Check if type is already defined in content
-fn type_already_exists<'a>(type_defs: Vec<&TypeDefinition>, type_name: &str) -> bool {
+fn type_already_exists(type_defs: Vec<&TypeDefinition>, type_name: &str) -> bool {
     type_defs.iter().any(|type_def| type_def.name == type_name)
 }
 
+/// Check if a type expression is a plain named type that can receive a placeholder,
+/// as opposed to an inline literal, array or union
+fn is_type_identifier(type_name: &str) -> bool {
+    if matches!(type_name, "string" | "number" | "boolean" | "void" | "unknown" | "object" | "Date" | "Blob" | "bigint") {
+        return false;
+    }
+    let mut chars = type_name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' || first == '$' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        }
+        _ => false,
+    }
+}
+
+/// Remove a directory and everything in it for `--clean`. Refuses the filesystem root, the
+/// home directory and the working directory or its ancestors, which cannot be generated output.
+fn clean_dir(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let dir = fs::canonicalize(path).with_context(|| format!("Failed to resolve directory: {}", path.display()))?;
+    let working_dir = std::env::current_dir().ok().and_then(|cwd| fs::canonicalize(cwd).ok());
+    let home_dir = std::env::var_os("HOME").and_then(|home| fs::canonicalize(home).ok());
+    let protected = dir.parent().is_none() ||
+        working_dir.is_some_and(|cwd| cwd.starts_with(&dir)) ||
+        home_dir.is_some_and(|home| home == dir);
+    if protected {
+        bail!("Refusing to clean {}: not a generated output directory", dir.display());
+    }
+    fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove directory: {}", dir.display()))
+}
+
 /// Ensure directory exists
 fn ensure_dir(path: &Path) -> Result<()> {
     if !path.exists() {
@@ -160,3 +884,292 @@ fn ensure_dir(path: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use serde_json::{ json, Value };
+
+    use crate::cli::{ DateType, Int64Type };
+    use crate::parser::{ parse_swagger, ParseOptions };
+
+    /// Parse a 3.0 spec with the given paths and schemas using default options
+    fn parse(paths: Value, schemas: Value) -> Vec<Service> {
+        let spec = json!({
+            "openapi": "3.0.0",
+            "paths": paths,
+            "components": { "schemas": schemas },
+        });
+        parse_swagger(&spec, &ParseOptions::default()).unwrap()
+    }
+
+    /// Command line arguments followed by `flags`
+    fn args(flags: &[&str]) -> Args {
+        Args::parse_from(["ropenapi", "-s", "api.json"].iter().chain(flags))
+    }
+
+    /// Operation taking an optional JSON body, answered with `202 Accepted`
+    fn optional_body_paths() -> Value {
+        json!({
+            "/jobs": {
+                "post": {
+                    "operationId": "startJob",
+                    "tags": ["jobs"],
+                    "requestBody": {
+                        "required": false,
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/JobRequest" } } }
+                    },
+                    "responses": { "202": { "description": "Accepted" } }
+                }
+            }
+        })
+    }
+
+    fn job_schemas() -> Value {
+        json!({ "JobRequest": { "type": "object", "properties": { "name": { "type": "string" } } } })
+    }
+
+    /// Contents of the files in `dir`, by file name
+    fn read_files(dir: &Path) -> BTreeMap<String, String> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                (path.file_name().unwrap().to_string_lossy().into_owned(), fs::read_to_string(&path).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn regeneration_overwrites_files() {
+        let out_dir = std::env::temp_dir().join(format!("ropenapi-regenerate-{}", std::process::id()));
+        let services = parse(optional_body_paths(), job_schemas());
+        let args = args(&["--index"]);
+
+        write_services(&out_dir, &services, &args).unwrap();
+        let first = read_files(&out_dir.join("project-swagger"));
+        write_services(&out_dir, &services, &args).unwrap();
+        let second = read_files(&out_dir.join("project-swagger"));
+        fs::remove_dir_all(&out_dir).unwrap();
+
+        assert_eq!(first.keys().collect::<Vec<_>>(), ["JobsController.ts", "index.ts"]);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn controller_suffix_is_added_once() {
+        let module = |name: &str| {
+            let service = Service { name: name.to_string(), operations: Vec::new(), type_definitions: BTreeMap::new() };
+            service_module_name(&service)
+        };
+        assert_eq!(module("user"), "UserController");
+        assert_eq!(module("UserController"), "UserController");
+        assert_eq!(module("usercontroller"), "Usercontroller");
+    }
+
+    #[test]
+    fn optional_body_signature() {
+        let services = parse(optional_body_paths(), job_schemas());
+        let content = render_api_with_request_lib(&services[0], &args(&[])).unwrap();
+        assert!(content.contains("export const startJob = async (data?: JobRequest): Promise<any> =>"));
+    }
+
+    #[test]
+    fn polling_helper_options_follow_required_arguments() {
+        let services = parse(optional_body_paths(), job_schemas());
+        let content = render_api_with_request_lib(&services[0], &args(&["--polling"])).unwrap();
+        assert!(
+            content.contains(
+                "export const pollStartJob = async <T = any>(data: JobRequest | undefined, options: PollOptions<T>): Promise<T> =>"
+            )
+        );
+
+        let content = render_api_with_request_lib(&services[0], &args(&["--polling", "--args-style", "object"])).unwrap();
+        assert!(
+            content.contains(
+                "export const pollStartJob = async <T = any>({ data }: StartJobArgs = {}, options: PollOptions<T>): Promise<T> =>"
+            )
+        );
+    }
+
+    #[test]
+    fn form_helpers_accept_undefined() {
+        let paths = json!({
+            "/upload": {
+                "post": {
+                    "operationId": "upload",
+                    "requestBody": {
+                        "content": { "multipart/form-data": { "schema": { "$ref": "#/components/schemas/JobRequest" } } }
+                    },
+                    "responses": { "200": { "description": "OK" } }
+                }
+            }
+        });
+        let services = parse(paths, job_schemas());
+        let content = render_api_with_request_lib(&services[0], &args(&[])).unwrap();
+        assert!(content.contains("const toFormData = (data?: object): FormData =>"));
+        assert!(QUERY_STRING_HELPER.starts_with("const toQueryString = (params?: object): string =>"));
+        assert!(URL_ENCODED_HELPER.starts_with("const toUrlEncoded = (data?: object): URLSearchParams =>"));
+    }
+
+    #[test]
+    fn polling_helpers_use_the_selected_client() {
+        let services = parse(optional_body_paths(), job_schemas());
+        let clients: [(&[&str], Option<&str>, &str); 3] = [
+            (
+                &[],
+                Some("import { request } from '@/services/request';"),
+                "const status = await request<any, any>({ url: options.statusUrl(initial), method: 'GET' });",
+            ),
+            (
+                &["--client", "axios"],
+                Some("import { axiosInstance } from '@/services/axios';"),
+                "const response = await axiosInstance.get<any>(options.statusUrl(initial));",
+            ),
+            (&["--client", "fetch"], None, "const response = await fetch(options.statusUrl(initial));"),
+        ];
+        for (flags, import, status_request) in clients {
+            let flags: Vec<&str> = flags.iter().copied().chain(["--polling"]).collect();
+            let content = render_api_with_request_lib(&services[0], &args(&flags)).unwrap();
+            assert!(content.contains(status_request), "{:?}", flags);
+            match import {
+                Some(import) => assert!(content.contains(import), "{:?}", flags),
+                // Nothing is imported, so nothing but fetch may be called
+                None => assert!(!content.contains("import ") && !content.contains("request<"), "{:?}", flags),
+            }
+            if flags.contains(&"axios") {
+                assert!(!content.contains("request<"));
+            }
+        }
+    }
+
+    #[test]
+    fn angular_polling_is_an_error() {
+        let services = parse(optional_body_paths(), job_schemas());
+        let err = render_api_with_request_lib(&services[0], &args(&["--client", "angular", "--polling"])).unwrap_err();
+        assert_eq!(err.to_string(), "--polling is not supported with --client angular");
+    }
+
+    #[test]
+    fn index_reexports_names_shared_by_services() {
+        let response = json!({ "content": { "application/json": { "schema": { "$ref": "#/components/schemas/User" } } } });
+        let paths = json!({
+            "/users": { "get": { "operationId": "listUsers", "tags": ["users"], "responses": { "200": response } } },
+            "/admins": { "get": { "operationId": "listAdmins", "tags": ["admins"], "responses": { "200": response } } },
+        });
+        let schemas = json!({ "User": { "type": "object", "properties": { "id": { "type": "string" } } } });
+        let services = parse(paths, schemas);
+        let out_dir = std::env::temp_dir().join(format!("ropenapi-index-{}", std::process::id()));
+        write_services(&out_dir, &services, &args(&["--index", "--routes"])).unwrap();
+        let files = read_files(&out_dir.join("project-swagger"));
+        fs::remove_dir_all(&out_dir).unwrap();
+        assert_eq!(
+            files["index.ts"],
+            "export * from './AdminsController';\n\
+             export * from './UsersController';\n\
+             export { routes } from './AdminsController';\n\
+             export type { User } from './AdminsController';\n"
+        );
+    }
+
+    #[test]
+    fn exported_names_and_kinds() {
+        let content = "export const getUser = async () => {};\n\
+                       export type User = { id: string };\n\
+                       export interface Page {}\n\
+                       export enum Role {}\n\
+                       const helper = 1;\n\
+                       export namespace Types {\n  export type A = string;\n}\n\
+                       export namespace Schemas {\n  export const ASchema = z.string();\n}\n";
+        assert_eq!(
+            exported_names(content),
+            [("getUser", false), ("User", true), ("Page", true), ("Role", false), ("Types", true), ("Schemas", false)]
+        );
+    }
+
+    #[test]
+    fn recursive_zod_schemas_are_annotated() {
+        let paths = json!({
+            "/nodes": {
+                "get": {
+                    "operationId": "getNode",
+                    "responses": {
+                        "200": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Node" } } } }
+                    }
+                }
+            }
+        });
+        let node_ref = json!({ "$ref": "#/components/schemas/Node" });
+        let schemas = json!({
+            "Node": {
+                "type": "object",
+                "required": ["children"],
+                "properties": { "children": { "type": "array", "items": node_ref }, "owner": { "$ref": "#/components/schemas/Owner" } }
+            },
+            "Owner": { "type": "object", "properties": { "node": node_ref, "tag": { "$ref": "#/components/schemas/Tag" } } },
+            "Tag": { "type": "object", "properties": { "name": { "type": "string" } } },
+        });
+        let services = parse(paths, schemas);
+        let content = render_types(&services[0], &args(&["--zod"]));
+
+        // A self-referential type and the member of a mutually recursive pair
+        assert!(content.contains("export const NodeSchema: z.ZodType<Node> = z.object("));
+        assert!(content.contains("export type Node = {"));
+        assert!(content.contains("export const OwnerSchema: z.ZodType<Owner> = z.object("));
+        assert!(!content.contains("typeof NodeSchema") && !content.contains("typeof OwnerSchema"));
+        // Types outside any cycle keep the inferred type
+        assert!(content.contains("export const TagSchema = z.object("));
+        assert!(content.contains("export type Tag = z.infer<typeof TagSchema>;"));
+    }
+
+    #[test]
+    fn bigint_responses_get_no_placeholder() {
+        let paths = json!({
+            "/count": {
+                "get": {
+                    "operationId": "count",
+                    "responses": {
+                        "200": {
+                            "content": { "application/json": { "schema": { "type": "integer", "format": "int64" } } }
+                        }
+                    }
+                }
+            }
+        });
+        let spec = json!({ "openapi": "3.0.0", "paths": paths });
+        let options = ParseOptions { int64: Int64Type::Bigint, ..Default::default() };
+        let services = parse_swagger(&spec, &options).unwrap();
+        assert_eq!(services[0].operations[0].response_type, "bigint");
+        assert!(placeholder_types(&services[0]).is_empty());
+    }
+
+    #[test]
+    fn guards_check_the_json_form_of_dates_and_bigints() {
+        let schemas = json!({
+            "Event": {
+                "type": "object",
+                "required": ["at", "id"],
+                "properties": {
+                    "at": { "type": "string", "format": "date-time" },
+                    "id": { "type": "integer", "format": "int64" },
+                }
+            }
+        });
+        let schema = json!({ "$ref": "#/components/schemas/Event" });
+        let paths = json!({
+            "/events": {
+                "get": { "responses": { "200": { "content": { "application/json": { "schema": schema } } } } }
+            }
+        });
+        let spec = json!({ "openapi": "3.0.0", "paths": paths, "components": { "schemas": schemas } });
+        let options = ParseOptions { date_type: DateType::Date, int64: Int64Type::Bigint, ..Default::default() };
+        let services = parse_swagger(&spec, &options).unwrap();
+        let event = &services[0].type_definitions["Event"];
+        assert_eq!(event.fields["at"].field_type, "Date");
+
+        let guard = guards::render_guard(event, &|_| false).unwrap();
+        assert!(guard.contains("typeof value.at === 'string'"));
+        assert!(guard.contains("(typeof value.id === 'number' || typeof value.id === 'bigint')"));
+    }
+}
@@ -0,0 +1,93 @@
+/// Lines of context shown around each change
+const CONTEXT: usize = 3;
+/// Largest line-count product diffed with the LCS table before giving up
+const MAX_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Edit {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// Render a unified diff between two versions of a file, with `CONTEXT` lines around changes
+pub fn unified_diff(old: &str, new: &str, name: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let mut out = format!("--- {}\n+++ {} (generated)\n", name, name);
+
+    if old_lines.len().saturating_mul(new_lines.len()) > MAX_CELLS {
+        out.push_str("@@ file too large to diff @@\n");
+        return out;
+    }
+
+    let edits = edit_script(&old_lines, &new_lines);
+
+    // Group the edits into hunks of changes separated by more than 2 * CONTEXT kept lines
+    let changes: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, (edit, _, _))| *edit != Edit::Keep)
+        .map(|(idx, _)| idx)
+        .collect();
+    let mut idx = 0;
+    while idx < changes.len() {
+        let start = changes[idx].saturating_sub(CONTEXT);
+        let mut end = changes[idx];
+        while idx + 1 < changes.len() && changes[idx + 1] - end <= 2 * CONTEXT {
+            idx += 1;
+            end = changes[idx];
+        }
+        let end = (end + CONTEXT + 1).min(edits.len());
+        idx += 1;
+
+        let hunk = &edits[start..end];
+        let old_start = hunk.iter().find_map(|(_, o, _)| *o).map_or(0, |o| o + 1);
+        let new_start = hunk.iter().find_map(|(_, _, n)| *n).map_or(0, |n| n + 1);
+        let old_count = hunk.iter().filter(|(e, _, _)| *e != Edit::Add).count();
+        let new_count = hunk.iter().filter(|(e, _, _)| *e != Edit::Remove).count();
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_count, new_start, new_count));
+        for (edit, o, n) in hunk {
+            match edit {
+                Edit::Keep => out.push_str(&format!(" {}\n", old_lines[o.unwrap()])),
+                Edit::Remove => out.push_str(&format!("-{}\n", old_lines[o.unwrap()])),
+                Edit::Add => out.push_str(&format!("+{}\n", new_lines[n.unwrap()])),
+            }
+        }
+    }
+    out
+}
+
+/// Shortest edit script between the two line lists from their longest common subsequence,
+/// as (edit, old line index, new line index) triples
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<(Edit, Option<usize>, Option<usize>)> {
+    let (n, m) = (old.len(), new.len());
+    // lcs[i][j]: length of the LCS of old[i..] and new[j..]
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            edits.push((Edit::Keep, Some(i), Some(j)));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            edits.push((Edit::Remove, Some(i), None));
+            i += 1;
+        } else {
+            edits.push((Edit::Add, None, Some(j)));
+            j += 1;
+        }
+    }
+    edits
+}
@@ -0,0 +1,94 @@
+use crate::models::TypeDefinition;
+use crate::naming::{ is_identifier, property_key };
+
+use crate::type_expr::{ enclosed, is_string_literal, split_top_level };
+
+/// Render `export const isUser = (value: any): value is User => ...` for an object type,
+/// checking that `value` is an object whose required fields have the expected `typeof`.
+/// Fields of a type with a guard of its own (`has_guard`) are checked with it; fields whose
+/// type cannot be checked only have to be present. `None` for non-object types.
+pub fn render_guard(type_def: &TypeDefinition, has_guard: &dyn Fn(&str) -> bool) -> Option<String> {
+    if !is_guarded(type_def) {
+        return None;
+    }
+    let mut checks = vec!["typeof value === 'object' && value !== null".to_string()];
+    for (field_name, field) in &type_def.fields {
+        if field.optional {
+            continue;
+        }
+        let access = if is_identifier(field_name) {
+            format!("value.{}", field_name)
+        } else {
+            format!("value[{}]", property_key(field_name))
+        };
+        checks.push(check(&field.field_type, &access, has_guard).unwrap_or_else(|| format!("{} !== undefined", access)));
+    }
+    Some(
+        format!(
+            "export const is{0} = (value: any): value is {0} =>\n  {1};",
+            type_def.name,
+            checks.join(" &&\n  ")
+        )
+    )
+}
+
+/// Whether a guard is rendered for the type: object schemas, not aliases or enums
+pub fn is_guarded(type_def: &TypeDefinition) -> bool {
+    type_def.alias.is_none() &&
+        type_def.enum_members.is_empty() &&
+        (type_def.is_object || !type_def.fields.is_empty() || type_def.additional_properties.is_some())
+}
+
+/// Runtime check of `access` against a TypeScript type expression, `None` when the type
+/// cannot be checked
+fn check(expr: &str, access: &str, has_guard: &dyn Fn(&str) -> bool) -> Option<String> {
+    let expr = expr.trim();
+
+    let members = split_top_level(expr, '|');
+    if members.len() > 1 {
+        let checks = members
+            .iter()
+            .map(|member| check(member, access, has_guard))
+            .collect::<Option<Vec<String>>>()?;
+        return Some(format!("({})", checks.join(" || ")));
+    }
+
+    let parts: Vec<&str> = split_top_level(expr, '&')
+        .into_iter()
+        // Brand markers such as `{ __min: 0 }` only exist at the type level
+        .filter(|p| !p.starts_with("{ __"))
+        .collect();
+    if parts.len() > 1 {
+        let checks: Vec<String> = parts
+            .iter()
+            .filter_map(|part| check(part, access, has_guard))
+            .collect();
+        return if checks.is_empty() { None } else { Some(checks.join(" && ")) };
+    }
+    let expr = parts.first().copied().unwrap_or(expr);
+
+    if expr.ends_with("[]") || expr.starts_with("Array<") {
+        return Some(format!("Array.isArray({})", access));
+    }
+    if let Some(inner) = enclosed(expr, '(', ')') {
+        return check(inner, access, has_guard);
+    }
+    if enclosed(expr, '{', '}').is_some() || expr.starts_with("Record<") {
+        return Some(format!("typeof {0} === 'object' && {0} !== null", access));
+    }
+    if is_string_literal(expr) || expr.parse::<f64>().is_ok() || expr == "true" || expr == "false" {
+        return Some(format!("{} === {}", access, expr));
+    }
+
+    match expr {
+        "string" | "number" | "boolean" => Some(format!("typeof {} === '{}'", access, expr)),
+        // Guards run on parsed JSON, where `--date-type Date` fields are still ISO strings and
+        // `--int64 bigint` ones numbers
+        "Date" => Some(format!("typeof {} === 'string'", access)),
+        "bigint" => Some(format!("(typeof {0} === 'number' || typeof {0} === 'bigint')", access)),
+        "null" => Some(format!("{} === null", access)),
+        "Blob" => Some(format!("{} instanceof Blob", access)),
+        name if has_guard(name) => Some(format!("is{}({})", name, access)),
+        _ => None,
+    }
+}
@@ -0,0 +1,88 @@
+mod react_query;
+mod swr;
+
+use crate::cli::{ ArgsStyle, Client };
+use crate::models::{ ApiOperation, Service };
+
+/// How a data-fetching library wraps the generated functions into hooks.
+/// Strategies share the argument and type resolution of `HookCall`.
+trait HookStrategy {
+    /// Import line of the library
+    fn imports(&self) -> &'static str;
+
+    /// Render the hook of an operation, or `None` when the library has none for it
+    fn render(&self, operation: &ApiOperation, call: &HookCall) -> Option<String>;
+}
+
+fn strategy(client: Client) -> &'static dyn HookStrategy {
+    match client {
+        Client::ReactQuery => &react_query::ReactQuery,
+        Client::Swr => &swr::Swr,
+        _ => unreachable!("{:?} is not a data-fetching library", client),
+    }
+}
+
+/// Import line of the hooks library of `client`
+pub fn imports(client: Client) -> &'static str {
+    strategy(client).imports()
+}
+
+/// Render the hooks of every operation of the service for `client`
+pub fn render_hooks(service: &Service, client: Client, args_style: ArgsStyle) -> Option<String> {
+    let strategy = strategy(client);
+    let hooks: Vec<String> = service.operations
+        .iter()
+        .filter_map(|op| strategy.render(op, &HookCall::new(op, args_style)))
+        .collect();
+
+    if hooks.is_empty() { None } else { Some(hooks.join("\n\n")) }
+}
+
+/// Parameters a hook takes and the arguments it forwards to the wrapped function,
+/// following the `--args-style` of the generated functions
+struct HookCall {
+    /// Typed hook parameters as (name, type) pairs
+    params: Vec<(String, String)>,
+    /// Argument expressions passed on to the wrapped function
+    call_args: Vec<String>,
+}
+
+impl HookCall {
+    fn new(operation: &ApiOperation, args_style: ArgsStyle) -> Self {
+        let arguments = operation.arguments();
+        match args_style {
+            ArgsStyle::Object if !arguments.is_empty() => HookCall {
+                params: vec![(
+                    if operation.all_arguments_optional() { "args?" } else { "args" }.to_string(),
+                    operation.args_type_name(),
+                )],
+                call_args: vec!["args".to_string()],
+            },
+            _ => HookCall {
+                call_args: arguments.iter().map(|(name, _)| name.clone()).collect(),
+                params: operation.parameters(ArgsStyle::Positional),
+            },
+        }
+    }
+
+    /// Typed parameter list, e.g. `userId: number, params: GetUsersParams`
+    fn signature(&self) -> String {
+        self.params
+            .iter()
+            .map(|(name, ts_type)| format!("{}: {}", name, ts_type))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Cache key of a read hook: the function name followed by its arguments
+    fn key(&self, operation: &ApiOperation) -> String {
+        let mut key = vec![format!("'{}'", operation.function_name)];
+        key.extend(self.call_args.iter().cloned());
+        format!("[{}]", key.join(", "))
+    }
+
+    /// Call of the wrapped function, e.g. `getUserById(userId)`
+    fn invocation(&self, operation: &ApiOperation) -> String {
+        format!("{}({})", operation.function_name, self.call_args.join(", "))
+    }
+}
@@ -0,0 +1,61 @@
+use super::{ HookCall, HookStrategy };
+use crate::formatting::block;
+use crate::models::ApiOperation;
+use crate::naming::capitalize_first;
+
+/// TanStack Query: GET and DELETE operations become `useQuery` hooks keyed by function name
+/// and arguments, the others `useMutation` hooks
+pub struct ReactQuery;
+
+impl HookStrategy for ReactQuery {
+    fn imports(&self) -> &'static str {
+        "import { useMutation, useQuery } from '@tanstack/react-query';"
+    }
+
+    fn render(&self, operation: &ApiOperation, call: &HookCall) -> Option<String> {
+        if matches!(operation.method.as_str(), "GET" | "DELETE") {
+            Some(render_query_hook(operation, call))
+        } else {
+            Some(render_mutation_hook(operation, call))
+        }
+    }
+}
+
+fn render_query_hook(operation: &ApiOperation, call: &HookCall) -> String {
+    let options = vec![
+        format!("queryKey: {},", call.key(operation)),
+        format!("queryFn: () => {},", call.invocation(operation))
+    ];
+    format!(
+        "export const use{} = ({}) => useQuery({});",
+        capitalize_first(&operation.function_name),
+        call.signature(),
+        block(&options)
+    )
+}
+
+fn render_mutation_hook(operation: &ApiOperation, call: &HookCall) -> String {
+    // Mutations receive a single variables value, so several arguments travel as one object
+    let mutation_fn = match call.params.as_slice() {
+        [] => format!("() => {}", call.invocation(operation)),
+        [(name, ts_type)] => format!("({}: {}) => {}", name, ts_type, call.invocation(operation)),
+        _ => {
+            let fields: Vec<String> = call.params
+                .iter()
+                .map(|(name, ts_type)| format!("{}: {}", name, ts_type))
+                .collect();
+            format!(
+                "({{ {} }}: {{ {} }}) => {}",
+                call.call_args.join(", "),
+                fields.join("; "),
+                call.invocation(operation)
+            )
+        }
+    };
+
+    format!(
+        "export const use{} = () => useMutation({});",
+        capitalize_first(&operation.function_name),
+        block(&[format!("mutationFn: {},", mutation_fn)])
+    )
+}
@@ -0,0 +1,28 @@
+use super::{ HookCall, HookStrategy };
+use crate::models::ApiOperation;
+use crate::naming::capitalize_first;
+
+/// SWR: `useSWR` hooks for GET operations, keyed by function name and arguments.
+/// Writes keep using the plain async functions.
+pub struct Swr;
+
+impl HookStrategy for Swr {
+    fn imports(&self) -> &'static str {
+        "import useSWR from 'swr';"
+    }
+
+    fn render(&self, operation: &ApiOperation, call: &HookCall) -> Option<String> {
+        if operation.method != "GET" {
+            return None;
+        }
+        Some(
+            format!(
+                "export const use{} = ({}) => useSWR({}, () => {});",
+                capitalize_first(&operation.function_name),
+                call.signature(),
+                call.key(operation),
+                call.invocation(operation)
+            )
+        )
+    }
+}
@@ -0,0 +1,149 @@
+use crate::formatting::block;
+use crate::models::TypeDefinition;
+use crate::naming::property_key;
+
+use crate::type_expr::{ enclosed, is_string_literal, split_top_level };
+
+/// How mock factories are rendered
+pub struct MockOptions<'a> {
+    /// Every type declared alongside, each getting a factory
+    pub type_defs: &'a [&'a TypeDefinition],
+    /// Fill optional fields too, instead of leaving them out
+    pub include_optional: bool,
+}
+
+/// Render `export const mockUser = (): User => ({ ... })` returning a plausible value of
+/// the type: empty strings, zeros, `false`, empty arrays and maps, and nested factories
+/// for referenced types. With `named_members` enum values are built as `Status.ACTIVE`.
+pub fn render_mock(type_def: &TypeDefinition, named_members: bool, options: &MockOptions) -> String {
+    let name = &type_def.name;
+    let value = if let (Some(member), true) = (type_def.enum_members.first(), named_members) {
+        format!("{}.{}", name, member.name)
+    } else if let Some(alias) = &type_def.alias {
+        // Aliases may be branded or narrowed in ways a plain value does not satisfy
+        format!("{} as {}", mock_value(alias, name, options), name)
+    } else if type_def.fields.is_empty() {
+        "{}".to_string()
+    } else {
+        let members: Vec<String> = type_def.fields
+            .iter()
+            .filter(|(_, field)| options.include_optional || !field.optional)
+            .map(|(field_name, field)| {
+                format!("{}: {},", property_key(field_name), mock_value(&field.field_type, name, options))
+            })
+            .collect();
+        block(&members)
+    };
+    // An arrow function returning an object literal needs it parenthesized
+    let body = if value.starts_with('{') { format!("({})", value) } else { value };
+    format!("export const mock{0} = (): {0} => {1};", name, body)
+}
+
+/// Plausible value of a TypeScript type expression, built inside the factory of `owner`
+fn mock_value(expr: &str, owner: &str, options: &MockOptions) -> String {
+    let expr = expr.trim();
+
+    let members = split_top_level(expr, '|');
+    if members.len() > 1 {
+        return members
+            .iter()
+            .find(|m| **m != "null")
+            .map(|m| mock_value(m, owner, options))
+            .unwrap_or_else(|| "null".to_string());
+    }
+
+    let parts: Vec<&str> = split_top_level(expr, '&')
+        .into_iter()
+        // Brand markers such as `{ __min: 0 }` only exist at the type level
+        .filter(|p| !p.starts_with("{ __"))
+        .collect();
+    if parts.len() > 1 {
+        let spreads: Vec<String> = parts
+            .iter()
+            .map(|p| format!("...{},", mock_value(p, owner, options)))
+            .collect();
+        return block(&spreads);
+    }
+    let expr = parts.first().copied().unwrap_or(expr);
+
+    if expr.ends_with("[]") || expr.starts_with("Array<") {
+        return "[]".to_string();
+    }
+    if let Some(inner) = enclosed(expr, '(', ')') {
+        return mock_value(inner, owner, options);
+    }
+    if let Some(inner) = enclosed(expr, '{', '}') {
+        return object_literal(inner, owner, options);
+    }
+    if expr.starts_with("Record<") {
+        return "{}".to_string();
+    }
+    if is_string_literal(expr) || expr.parse::<f64>().is_ok() || expr == "true" || expr == "false" {
+        return expr.to_string();
+    }
+
+    match expr {
+        "string" => "''".to_string(),
+        "number" => "0".to_string(),
+        "bigint" => "BigInt(0)".to_string(),
+        "boolean" => "false".to_string(),
+        "Date" => "new Date(0)".to_string(),
+        "Blob" => "new Blob()".to_string(),
+        // A factory leading back to the type being built would recurse forever
+        name if declares(options, name) && !reaches(name, owner, options, &mut Vec::new()) => {
+            format!("mock{}()", name)
+        }
+        name if declares(options, name) => format!("{{}} as {}", name),
+        _ => "null".to_string(),
+    }
+}
+
+fn declares(options: &MockOptions, name: &str) -> bool {
+    options.type_defs.iter().any(|type_def| type_def.name == name)
+}
+
+/// Whether the factory of `from` ends up calling the factory of `to`, following the
+/// (over-approximated) names its filled fields refer to
+fn reaches<'a>(from: &'a str, to: &str, options: &'a MockOptions, visited: &mut Vec<&'a str>) -> bool {
+    if from == to {
+        return true;
+    }
+    if visited.contains(&from) {
+        return false;
+    }
+    visited.push(from);
+    let Some(type_def) = options.type_defs.iter().find(|type_def| type_def.name == from) else {
+        return false;
+    };
+    let field_types = type_def.fields
+        .values()
+        .filter(|field| options.include_optional || !field.optional)
+        .map(|field| field.field_type.as_str());
+    type_def.alias
+        .as_deref()
+        .into_iter()
+        .chain(field_types)
+        .flat_map(|expr| expr.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$')))
+        .any(|name| !name.is_empty() && reaches(name, to, options, visited))
+}
+
+/// Value of an inline object literal body such as `id: number; name?: string`
+fn object_literal(body: &str, owner: &str, options: &MockOptions) -> String {
+    let members: Vec<String> = split_top_level(body, ';')
+        .into_iter()
+        .filter(|m| !m.is_empty())
+        .filter_map(|member| {
+            let (key, field_type) = split_top_level(member, ':')
+                .split_first()
+                .map(|(key, rest)| (*key, rest.join(":")))?;
+            let (key, optional) = match key.strip_suffix('?') {
+                Some(key) => (key, true),
+                None => (key, false),
+            };
+            (options.include_optional || !optional).then(|| {
+                format!("{}: {},", key, mock_value(&field_type, owner, options))
+            })
+        })
+        .collect();
+    block(&members)
+}
@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use super::{
+    imported_type_names,
+    namespace_service,
+    placeholder_types,
+    service_file_stem,
+    service_module_name,
+    SHARED_TYPES_MODULE,
+};
+use crate::cli::FileCase;
+use crate::formatting::block;
+use crate::models::{ ApiOperation, Service };
+use crate::naming::property_key;
+
+/// Render `client.ts` for openapi-fetch 0.12+: an openapi-typescript (v7) style `paths`
+/// interface covering every service, and a client created from it.
+/// Types are imported from the service file that first declares them, or from the
+/// `types.ts` module with `shared_types`. With `namespace`, the namespace is imported instead,
+/// aliased per service file.
+pub fn render_client(
+    services: &[Service],
+    api_prefix: &str,
+    namespace: Option<&str>,
+    shared_types: bool,
+    file_case: FileCase
+) -> String {
+    let namespaced: Vec<Service>;
+    let (imports, services) = match namespace {
+        Some(namespace) if shared_types => {
+            namespaced = services
+                .iter()
+                .map(|service| namespace_service(service, namespace))
+                .collect();
+            (vec![format!("import type {{ {} }} from './{}';", namespace, SHARED_TYPES_MODULE)], namespaced.as_slice())
+        }
+        Some(namespace) => {
+            let mut imports: Vec<String> = Vec::new();
+            for service in services {
+                if !service.type_definitions.is_empty() || !placeholder_types(service).is_empty() {
+                    imports.push(
+                        format!(
+                            "import type {{ {} as {}Types }} from './{}';",
+                            namespace,
+                            service_module_name(service),
+                            service_file_stem(service, file_case)
+                        )
+                    );
+                }
+            }
+            namespaced = services
+                .iter()
+                .map(|service| namespace_service(service, &format!("{}Types", service_module_name(service))))
+                .collect();
+            (imports, namespaced.as_slice())
+        }
+        None => (named_imports(services, shared_types, file_case), services),
+    };
+
+    // Operations of one path can be spread over several services, and with `--multi-tag`
+    // the same operation can appear in more than one
+    let mut paths: BTreeMap<&str, Vec<(&str, String)>> = BTreeMap::new();
+    for operation in services.iter().flat_map(|s| &s.operations) {
+        let methods = paths.entry(&operation.path).or_default();
+        if !methods.iter().any(|(method, _)| *method == operation.method) {
+            methods.push((&operation.method, render_operation(operation)));
+        }
+    }
+    let path_members: Vec<String> = paths
+        .iter()
+        .map(|(path, methods)| {
+            let members: Vec<String> = methods.iter().map(|(_, member)| member.clone()).collect();
+            format!("'{}': {};", path, block(&members))
+        })
+        .collect();
+
+    let mut content = String::from("import createClient from 'openapi-fetch';\n");
+    for import in &imports {
+        content.push_str(import);
+        content.push('\n');
+    }
+    content.push_str(&format!("\nexport interface paths {}\n\n", block(&path_members)));
+    content.push_str(&format!("export const client = createClient<paths>({{ baseUrl: '{}' }});\n", api_prefix));
+    content
+}
+
+/// `import type { A, B } from './XController'` lines importing every declared type the
+/// operations use from the first service declaring it, or one import from `types.ts`
+/// with `shared_types`
+fn named_imports(services: &[Service], shared_types: bool, file_case: FileCase) -> Vec<String> {
+    let mut imported: Vec<&str> = Vec::new();
+    let mut imports: Vec<String> = Vec::new();
+    for service in services {
+        let mut names = imported_type_names(service);
+        names.retain(|name| !imported.contains(name));
+        imported.extend(&names);
+        if !names.is_empty() && !shared_types {
+            imports.push(
+                format!("import type {{ {} }} from './{}';", names.join(", "), service_file_stem(service, file_case))
+            );
+        }
+    }
+    if shared_types && !imported.is_empty() {
+        imports.push(format!("import type {{ {} }} from './{}';", imported.join(", "), SHARED_TYPES_MODULE));
+    }
+    imports
+}
+
+/// Render the `get: { parameters; requestBody; responses }` member of a path
+fn render_operation(operation: &ApiOperation) -> String {
+    let mut parameters: Vec<String> = Vec::new();
+    if let Some(query_type) = &operation.query_type {
+        parameters.push(format!("query?: {};", query_type));
+    }
+    if !operation.path_params.is_empty() {
+        let path_fields: Vec<String> = operation.path_params
+            .iter()
+            .map(|p| format!("{}: {}", property_key(&p.name), p.field_type))
+            .collect();
+        parameters.push(format!("path: {{ {} }};", path_fields.join("; ")));
+    }
+    if !operation.header_params.is_empty() {
+        let header_fields: Vec<String> = operation.header_params
+            .iter()
+            .map(|p| format!("{}{}: {}", property_key(&p.name), if p.optional { "?" } else { "" }, p.field_type))
+            .collect();
+        parameters.push(format!("header: {{ {} }};", header_fields.join("; ")));
+    }
+
+    let mut members: Vec<String> = Vec::new();
+    if !parameters.is_empty() {
+        members.push(format!("parameters: {};", block(&parameters)));
+    }
+    if let Some(body_type) = &operation.body_type {
+        members.push(
+            format!(
+                "requestBody: {{ content: {{ '{}': {} }} }};",
+                operation.body_encoding.content_type(),
+                body_type
+            )
+        );
+    }
+    if operation.returns_void() {
+        members.push("responses: { 204: { content?: never } };".to_string());
+    } else {
+        members.push(
+            format!(
+                "responses: {{ 200: {{ content: {{ 'application/json': {} }} }} }};",
+                operation.response_ts_type()
+            )
+        );
+    }
+
+    format!("{}: {};", operation.method.to_lowercase(), block(&members))
+}
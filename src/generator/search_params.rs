@@ -0,0 +1,73 @@
+use crate::formatting::block;
+use crate::models::{ ApiOperation, Service };
+use crate::naming::{ capitalize_first, property_key };
+
+/// Coercion helpers shared by the generated `parseXxxParams` functions
+const COERCION_HELPERS: &str = "const toNumber = (value: string | null): number | undefined => {
+  if (value === null || value.trim() === '') return undefined;
+  const parsed = Number(value);
+  return Number.isNaN(parsed) ? undefined : parsed;
+};
+
+const toBoolean = (value: string | null): boolean | undefined =>
+  value === 'true' ? true : value === 'false' ? false : undefined;";
+
+/// Render `parseXxxParams(search: URLSearchParams)` functions for every GET operation
+/// whose params type is a named definition, converting each value to its declared type.
+/// Return types are qualified with `namespace` when the types are wrapped in one.
+pub fn render_search_param_parsers(service: &Service, namespace: Option<&str>) -> Option<String> {
+    let parsers: Vec<String> = service.operations
+        .iter()
+        .filter(|op| op.method == "GET")
+        .filter_map(|op| render_parser(op, service, namespace))
+        .collect();
+
+    if parsers.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}\n\n{}", COERCION_HELPERS, parsers.join("\n\n")))
+}
+
+fn render_parser(operation: &ApiOperation, service: &Service, namespace: Option<&str>) -> Option<String> {
+    let query_type = operation.query_type.as_ref()?;
+    let type_def = service.type_definitions.get(query_type)?;
+    if type_def.fields.is_empty() {
+        return None;
+    }
+
+    let members: Vec<String> = type_def.fields
+        .iter()
+        .map(|(name, field)| {
+            let value = coerce(name, &field.field_type);
+            let value = if field.optional { value } else { format!("({})!", value) };
+            format!("{}: {},", property_key(name), value)
+        })
+        .collect();
+
+    Some(
+        format!(
+            "export const parse{}Params = (search: URLSearchParams): {} => ({});",
+            capitalize_first(&operation.function_name),
+            match namespace {
+                Some(namespace) => format!("{}.{}", namespace, query_type),
+                None => query_type.clone(),
+            },
+            block(&members)
+        )
+    )
+}
+
+/// Expression converting the raw search value(s) of `name` into `field_type`
+fn coerce(name: &str, field_type: &str) -> String {
+    let key = format!("'{}'", name.replace('\'', "\\'"));
+    match field_type {
+        "number" => format!("toNumber(search.get({}))", key),
+        "boolean" => format!("toBoolean(search.get({}))", key),
+        "string" => format!("search.get({}) ?? undefined", key),
+        "number[]" => format!("search.getAll({}).map(Number)", key),
+        "boolean[]" => format!("search.getAll({}).map((v) => v === 'true')", key),
+        "string[]" => format!("search.getAll({})", key),
+        _ => format!("(search.get({}) ?? undefined) as any", key),
+    }
+}
@@ -0,0 +1,82 @@
+mod angular;
+mod axios;
+mod fetch;
+mod request;
+
+use anyhow::Result;
+
+use crate::cli::Client;
+use crate::models::{ ApiOperation, FunctionOptions, Service };
+
+/// How the generated functions send their requests. Strategies share the URL, header
+/// and return type resolution of `ApiOperation`.
+trait Transport {
+    /// Import line used when `--request-lib-path` is not given, `None` for built-in clients
+    fn default_import(&self) -> Option<&'static str>;
+
+    /// Whether query params have to be serialized into the URL, the client taking no params option
+    fn query_in_url(&self) -> bool {
+        false
+    }
+
+    /// Framework imports the module needs whatever the request lib, e.g. Angular's `HttpClient`
+    fn imports(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Statements of a `--polling` helper assigning the response of a GET to `url`, an
+    /// expression, to `status`. An error for clients that cannot poll.
+    fn status_request(&self, url: &str) -> Result<Vec<String>>;
+
+    /// Statements of the function body sending the request of `operation`
+    fn body(&self, operation: &ApiOperation, options: &FunctionOptions) -> Vec<String>;
+
+    /// Render the functions of `service`, each operation with its own options: exported
+    /// functions by default
+    fn render_functions(&self, _service: &Service, operations: &[(&ApiOperation, FunctionOptions)]) -> String {
+        operations
+            .iter()
+            .map(|(operation, options)| operation.to_typescript_function(options, &self.body(operation, options)))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+fn strategy(client: Option<Client>) -> &'static dyn Transport {
+    match client {
+        Some(Client::Axios) => &axios::Axios,
+        Some(Client::Fetch) => &fetch::Fetch,
+        Some(Client::Angular) => &angular::Angular,
+        _ => &request::Request,
+    }
+}
+
+/// Import line of the HTTP client, unless overridden by `--request-lib-path`
+pub fn default_import(client: Option<Client>) -> Option<&'static str> {
+    strategy(client).default_import()
+}
+
+/// Framework imports of `client`, written along with the request lib
+pub fn imports(client: Option<Client>) -> Option<&'static str> {
+    strategy(client).imports()
+}
+
+/// Whether `client` needs the query serialized into the URL, as with `--query-string`
+pub fn query_in_url(client: Option<Client>) -> bool {
+    strategy(client).query_in_url()
+}
+
+/// Statements of a `--polling` helper fetching the status at `url` into `status` with `client`
+pub fn status_request(client: Option<Client>, url: &str) -> Result<Vec<String>> {
+    strategy(client).status_request(url)
+}
+
+/// Render the functions of the operations of `service` for the HTTP client `client`
+/// (the `request` wrapper when `None`)
+pub fn render_functions(
+    service: &Service,
+    client: Option<Client>,
+    operations: &[(&ApiOperation, FunctionOptions)]
+) -> String {
+    strategy(client).render_functions(service, operations)
+}
@@ -0,0 +1,94 @@
+use anyhow::{ bail, Result };
+
+use super::Transport;
+use crate::formatting::block;
+use crate::models::{ ApiOperation, FunctionOptions, Service };
+use crate::naming::to_pascal_identifier;
+
+/// An `@Injectable` `XxxService` class per service whose methods return the observables of
+/// `this.http.get<Response>(url, options)` on Angular's `HttpClient`
+pub struct Angular;
+
+impl Transport for Angular {
+    fn default_import(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn imports(&self) -> Option<&'static str> {
+        Some(
+            "import { Injectable } from '@angular/core';\n\
+             import { HttpClient } from '@angular/common/http';\n\
+             import { Observable } from 'rxjs';"
+        )
+    }
+
+    fn status_request(&self, _url: &str) -> Result<Vec<String>> {
+        bail!("--polling is not supported with --client angular")
+    }
+
+    fn body(&self, operation: &ApiOperation, options: &FunctionOptions) -> Vec<String> {
+        let mut config: Vec<String> = Vec::new();
+        if operation.query_type.is_some() && !options.query_string {
+            config.push("params".to_string());
+        }
+        if let Some(headers) = operation.headers_expression(options) {
+            config.push(format!("headers: {}", headers));
+        }
+
+        let method = operation.method.to_lowercase();
+        let mut call_args = vec![operation.request_url(options)];
+        match method.as_str() {
+            // Verbs whose HttpClient methods take the body as second argument
+            "post" | "put" | "patch" => {
+                let data = if operation.body_type.is_some() { operation.body_expression() } else { "null" };
+                call_args.push(data.to_string());
+            }
+            _ if operation.body_type.is_some() => config.push(format!("body: {}", operation.body_expression())),
+            _ => {}
+        }
+        if !config.is_empty() {
+            call_args.push(format!("{{ {} }}", config.join(", ")));
+        }
+
+        let call = match method.as_str() {
+            "get" | "post" | "put" | "patch" | "delete" | "head" | "options" => {
+                format!("this.http.{}<{}>({})", method, operation.return_type(options), call_args.join(", "))
+            }
+            // Other verbs go through the generic `request`, which takes the body in the options
+            _ => format!(
+                "this.http.request<{}>('{}', {})",
+                operation.return_type(options),
+                operation.method,
+                call_args.join(", ")
+            ),
+        };
+        vec![format!("return {};", call)]
+    }
+
+    fn render_functions(&self, service: &Service, operations: &[(&ApiOperation, FunctionOptions)]) -> String {
+        let mut declarations: Vec<String> = Vec::new();
+        let mut methods: Vec<String> = vec!["constructor(private http: HttpClient) {}".to_string()];
+        for (operation, options) in operations {
+            // Observables are cancelled by unsubscribing, so there is no signal argument
+            let options = FunctionOptions { abort_signal: false, ..*options };
+            declarations.extend(operation.declarations(&options));
+            let doc = operation.doc_comment(options.source_comment);
+            let method = format!(
+                "{}({}): Observable<{}> {}",
+                operation.function_name,
+                operation.parameter_list(options.args_style, false),
+                operation.return_type(&options),
+                block(&self.body(operation, &options))
+            );
+            methods.push(if doc.is_empty() { method } else { format!("{}\n{}", doc, method) });
+        }
+        declarations.push(
+            format!(
+                "@Injectable({{ providedIn: 'root' }})\nexport class {}Service {}",
+                to_pascal_identifier(&service.name),
+                block(&[methods.join("\n\n")])
+            )
+        );
+        declarations.join("\n\n")
+    }
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+
+use super::Transport;
+use crate::models::{ ApiOperation, FunctionOptions };
+
+/// `axiosInstance.get<Response>(url, config)` on a shared axios instance, resolving to the
+/// response `data`
+pub struct Axios;
+
+impl Transport for Axios {
+    fn default_import(&self) -> Option<&'static str> {
+        Some("import { axiosInstance } from '@/services/axios';")
+    }
+
+    fn status_request(&self, url: &str) -> Result<Vec<String>> {
+        Ok(vec![
+            format!("const response = await axiosInstance.get<any>({});", url),
+            "const status = response.data;".to_string(),
+        ])
+    }
+
+    fn body(&self, operation: &ApiOperation, options: &FunctionOptions) -> Vec<String> {
+        let mut config: Vec<String> = Vec::new();
+        if operation.query_type.is_some() && !options.query_string {
+            config.push("params".to_string());
+        }
+        if let Some(headers) = operation.headers_expression(options) {
+            config.push(format!("headers: {}", headers));
+        }
+        if options.abort_signal {
+            config.push("signal".to_string());
+        }
+
+        let method = operation.method.to_lowercase();
+        let mut call_args = vec![operation.request_url(options)];
+        match method.as_str() {
+            // Verbs whose axios helpers take the body as second argument
+            "post" | "put" | "patch" => {
+                let data = if operation.body_type.is_some() { operation.body_expression() } else { "undefined" };
+                call_args.push(data.to_string());
+            }
+            _ if operation.body_type.is_some() => config.push(format!("data: {}", operation.body_expression())),
+            _ => {}
+        }
+        if !config.is_empty() {
+            call_args.push(format!("{{ {} }}", config.join(", ")));
+        }
+
+        vec![
+            format!(
+                "const response = await axiosInstance.{}<{}>({});",
+                method,
+                operation.return_type(options),
+                call_args.join(", ")
+            ),
+            "return response.data;".to_string(),
+        ]
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::Result;
+
+use super::Transport;
+use crate::formatting::block;
+use crate::models::{ ApiOperation, BodyEncoding, FunctionOptions };
+
+/// Native `fetch(url, { method, headers, body })`, throwing on non-OK responses
+pub struct Fetch;
+
+impl Transport for Fetch {
+    fn default_import(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn query_in_url(&self) -> bool {
+        true
+    }
+
+    fn status_request(&self, url: &str) -> Result<Vec<String>> {
+        Ok(vec![
+            format!("const response = await fetch({});", url),
+            format!(
+                "if (!response.ok) {}",
+                block(&["throw new Error(`${response.status} ${response.statusText}`);".to_string()])
+            ),
+            "const status = await response.json();".to_string(),
+        ])
+    }
+
+    fn body(&self, operation: &ApiOperation, options: &FunctionOptions) -> Vec<String> {
+        let mut headers: Vec<String> = Vec::new();
+        // Form bodies set their content type themselves, with the multipart boundary
+        if operation.body_type.is_some() && operation.body_encoding == BodyEncoding::Json {
+            headers.push("'Content-Type': 'application/json'".to_string());
+        }
+        headers.extend(operation.header_entries(options));
+
+        let mut init = vec![format!("method: '{}',", operation.method)];
+        if !headers.is_empty() {
+            init.push(format!("headers: {{ {} }},", headers.join(", ")));
+        }
+        match operation.body_encoding {
+            _ if operation.body_type.is_none() => {}
+            BodyEncoding::Json => init.push("body: JSON.stringify(data),".to_string()),
+            BodyEncoding::FormUrlEncoded | BodyEncoding::Multipart => {
+                init.push(format!("body: {},", operation.body_expression()));
+            }
+        }
+        if options.abort_signal {
+            init.push("signal,".to_string());
+        }
+
+        let mut body = vec![
+            format!("const response = await fetch({}, {});", operation.request_url(options), block(&init)),
+            format!(
+                "if (!response.ok) {}",
+                block(&["throw new Error(`${response.status} ${response.statusText}`);".to_string()])
+            )
+        ];
+        // An empty body cannot be parsed as JSON
+        if !operation.returns_void() {
+            body.push(format!("return response.json() as Promise<{}>;", operation.return_type(options)));
+        }
+        body
+    }
+}
@@ -0,0 +1,44 @@
+use anyhow::Result;
+
+use super::Transport;
+use crate::formatting::block;
+use crate::models::{ ApiOperation, FunctionOptions };
+
+/// `request<Payload, Response>({ url, params, data, headers, method })` of a project wrapper
+pub struct Request;
+
+impl Transport for Request {
+    fn default_import(&self) -> Option<&'static str> {
+        Some("import { request } from '@/services/request';")
+    }
+
+    fn status_request(&self, url: &str) -> Result<Vec<String>> {
+        Ok(vec![format!("const status = await request<any, any>({{ url: {}, method: 'GET' }});", url)])
+    }
+
+    fn body(&self, operation: &ApiOperation, options: &FunctionOptions) -> Vec<String> {
+        let mut config = vec![format!("url: {},", operation.request_url(options))];
+        if operation.query_type.is_some() && !options.query_string {
+            config.push("params: params,".to_string());
+        }
+        if operation.body_type.is_some() {
+            config.push(format!("data: {},", operation.body_expression()));
+        }
+        if let Some(headers) = operation.headers_expression(options) {
+            config.push(format!("headers: {},", headers));
+        }
+        config.push(format!("method: '{}',", operation.method));
+        if options.abort_signal {
+            config.push("signal: signal,".to_string());
+        }
+
+        vec![
+            format!(
+                "return request<{}, {}>({});",
+                operation.payload_type(),
+                operation.return_type(options),
+                block(&config)
+            ),
+        ]
+    }
+}
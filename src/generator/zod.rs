@@ -0,0 +1,184 @@
+use std::collections::{ HashMap, HashSet };
+
+use crate::formatting::block;
+use crate::models::TypeDefinition;
+use crate::naming::property_key;
+use crate::type_expr::{ enclosed, is_string_literal, split_top_level };
+
+use super::type_identifiers;
+
+/// Import of the Zod runtime used by the generated schemas
+pub const IMPORT: &str = "import { z } from 'zod';";
+
+/// Render `export const UserSchema = z.object({ ... })` for a type definition, with its
+/// TypeScript type inferred from the schema. `declared` tells which names have a schema
+/// of their own; other named types are not validated (`z.any()`). A recursive type cannot
+/// be inferred from its own schema, so `type_declaration` declares it instead and the schema
+/// is annotated with it: `export const NodeSchema: z.ZodType<Node> = ...`.
+pub fn render_schema(
+    type_def: &TypeDefinition,
+    declared: &dyn Fn(&str) -> bool,
+    type_declaration: Option<&str>
+) -> String {
+    let record_value = type_def.additional_properties.as_deref().map(|v| to_zod(v, declared));
+    let schema = if let Some(alias) = &type_def.alias {
+        to_zod(alias, declared)
+    } else if type_def.fields.is_empty() {
+        match record_value {
+            Some(value) => format!("z.record(z.string(), {})", value),
+            None => "z.any()".to_string(),
+        }
+    } else {
+        let members: Vec<String> = type_def.fields
+            .iter()
+            .map(|(name, field)| {
+                let optional = if field.optional { ".optional()" } else { "" };
+                format!("{}: {}{},", property_key(name), to_zod(&field.field_type, declared), optional)
+            })
+            .collect();
+        let object = format!("z.object({})", block(&members));
+        match record_value {
+            Some(value) => format!("{}.catchall({})", object, value),
+            None => object,
+        }
+    };
+    match type_declaration {
+        Some(type_declaration) => format!(
+            "{}\n\nexport const {1}Schema: z.ZodType<{1}> = {2};",
+            type_declaration,
+            type_def.name,
+            schema
+        ),
+        None => declaration(&type_def.name, &schema),
+    }
+}
+
+/// Schema of a placeholder type, which accepts anything
+pub fn render_placeholder(name: &str) -> String {
+    declaration(name, "z.any()")
+}
+
+fn declaration(name: &str, schema: &str) -> String {
+    format!(
+        "export const {0}Schema = {1};\n\nexport type {0} = z.infer<typeof {0}Schema>;",
+        name,
+        schema
+    )
+}
+
+/// Names of the types of `type_defs` referring back to themselves, directly or through
+/// other types of `type_defs`
+pub fn recursive_types<'a>(type_defs: &[&'a TypeDefinition]) -> HashSet<&'a str> {
+    let references: HashMap<&str, Vec<&str>> = type_defs
+        .iter()
+        .map(|type_def| {
+            let names = type_def.fields
+                .values()
+                .map(|field| field.field_type.as_str())
+                .chain(type_def.alias.as_deref())
+                .chain(type_def.additional_properties.as_deref())
+                .flat_map(type_identifiers)
+                .collect();
+            (type_def.name.as_str(), names)
+        })
+        .collect();
+
+    let reaches_itself = |start: &str| {
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut pending: Vec<&str> = references[start].clone();
+        while let Some(name) = pending.pop() {
+            if name == start {
+                return true;
+            }
+            if visited.insert(name) {
+                pending.extend(references.get(name).into_iter().flatten());
+            }
+        }
+        false
+    };
+    type_defs
+        .iter()
+        .map(|type_def| type_def.name.as_str())
+        .filter(|name| reaches_itself(name))
+        .collect()
+}
+
+/// Translate a TypeScript type expression as produced by the parser into a Zod schema
+fn to_zod(expr: &str, declared: &dyn Fn(&str) -> bool) -> String {
+    let expr = expr.trim();
+
+    let members = split_top_level(expr, '|');
+    if members.len() > 1 {
+        let nullable = members.contains(&"null");
+        let members: Vec<&str> = members.into_iter().filter(|m| *m != "null").collect();
+        let schema = if members.iter().all(|m| is_string_literal(m)) {
+            format!("z.enum([{}])", members.join(", "))
+        } else if let [member] = members.as_slice() {
+            to_zod(member, declared)
+        } else {
+            let schemas: Vec<String> = members.iter().map(|m| to_zod(m, declared)).collect();
+            format!("z.union([{}])", schemas.join(", "))
+        };
+        return if nullable { format!("{}.nullable()", schema) } else { schema };
+    }
+
+    let parts = split_top_level(expr, '&');
+    if parts.len() > 1 {
+        // Brand markers such as `{ __min: 0 }` only exist at the type level
+        return parts
+            .iter()
+            .filter(|p| !p.starts_with("{ __"))
+            .map(|p| to_zod(p, declared))
+            .reduce(|acc, next| format!("z.intersection({}, {})", acc, next))
+            .unwrap_or_default();
+    }
+
+    if let Some(element) = expr.strip_suffix("[]") {
+        return format!("z.array({})", to_zod(element, declared));
+    }
+    if let Some(inner) = enclosed(expr, '(', ')') {
+        return to_zod(inner, declared);
+    }
+    if let Some(inner) = enclosed(expr, '{', '}') {
+        return object_literal(inner, declared);
+    }
+    if let Some(value) = expr.strip_prefix("Record<string,").and_then(|rest| rest.strip_suffix('>')) {
+        return format!("z.record(z.string(), {})", to_zod(value, declared));
+    }
+    if is_string_literal(expr) || expr.parse::<f64>().is_ok() || expr == "true" || expr == "false" {
+        return format!("z.literal({})", expr);
+    }
+
+    match expr {
+        "string" => "z.string()".to_string(),
+        "number" => "z.number()".to_string(),
+        "bigint" => "z.bigint()".to_string(),
+        "boolean" => "z.boolean()".to_string(),
+        "null" => "z.null()".to_string(),
+        "Date" => "z.coerce.date()".to_string(),
+        "Blob" => "z.instanceof(Blob)".to_string(),
+        "unknown" => "z.unknown()".to_string(),
+        // Referenced lazily so declaration order and recursive types do not matter
+        name if declared(name) => format!("z.lazy(() => {}Schema)", name),
+        _ => "z.any()".to_string(),
+    }
+}
+
+/// Schema of an inline object literal body such as `id: number; name?: string`
+fn object_literal(body: &str, declared: &dyn Fn(&str) -> bool) -> String {
+    let members: Vec<String> = split_top_level(body, ';')
+        .into_iter()
+        .filter(|m| !m.is_empty())
+        .filter_map(|member| {
+            let (key, field_type) = split_top_level(member, ':')
+                .split_first()
+                .map(|(key, rest)| (*key, rest.join(":")))?;
+            let (key, optional) = match key.strip_suffix('?') {
+                Some(key) => (key, ".optional()"),
+                None => (key, ""),
+            };
+            Some(format!("{}: {}{},", key, to_zod(&field_type, declared), optional))
+        })
+        .collect();
+    format!("z.object({})", block(&members))
+}
@@ -1,34 +1,96 @@
 mod cli;
+mod formatting;
 mod models;
+mod naming;
 mod parser;
 mod generator;
+mod template;
+mod type_expr;
+mod watch;
 
 use anyhow::Result;
+use serde_json::Value;
 use std::collections::HashSet;
 use std::path::PathBuf;
 
 fn main() -> Result<()> {
-    let args = cli::parse_args();
+    let args = cli::parse_args()?;
 
+    if args.watch {
+        return watch::run(&args);
+    }
+
+    let specs = read_specs(&args)?;
+    generate(&args, &specs)
+}
+
+/// Read every `--swagger` file or URL
+fn read_specs(args: &cli::Args) -> Result<Vec<Value>> {
+    args.swagger
+        .iter()
+        .map(|source| {
+            if args.streaming {
+                parser::read_swagger_streaming(source, &args.header, args.pretty_json_errors, args.verbose)
+            } else {
+                parser::read_swagger_file(source, &args.header, args.pretty_json_errors, args.verbose)
+            }
+        })
+        .collect()
+}
+
+/// Parse the specs, merging their services by tag, and write the generated services
+fn generate(args: &cli::Args, specs: &[Value]) -> Result<()> {
     let out_dir = args.out
         .clone()
         .or_else(|| { args.service_path.clone() })
         .map(PathBuf::from)
         .unwrap_or_else(|| PathBuf::from("./services"));
 
-    let swagger_json = parser::read_swagger_file(&args.swagger)?;
+    let tag_filters = args.tags.as_deref().map(tag_set);
+    let excluded_tags = args.exclude_tags.as_deref().map(tag_set).unwrap_or_default();
+
+    let parse_options = parser::ParseOptions {
+        tag_filters,
+        excluded_tags,
+        inline_small_types: args.inline_small_types,
+        branded_ranges: args.branded_ranges,
+        operation_name_from: args.operation_name_from,
+        date_type: args.date_type,
+        int64: args.int64,
+        keep_duplicate_names: args.validate_unique_operation_names.is_some(),
+        multi_tag: args.multi_tag,
+        omit_readonly_in_request: args.omit_readonly_in_request,
+        omit_writeonly_in_response: args.omit_writeonly_in_response,
+        camel_fields: args.camel_fields,
+        union_success_responses: args.union_success_responses,
+        error_types: args.error_types,
+        preserve_tag_case: args.preserve_tag_case,
+    };
 
-    let tag_filters: Option<HashSet<String>> = args.tags.as_ref().map(|s| {
-        s.split(',')
-            .map(|t| t.trim().to_string())
-            .filter(|t| !t.is_empty())
-            .collect()
-    });
+    let documents = specs
+        .iter()
+        .map(|spec| parser::parse_swagger(spec, &parse_options))
+        .collect::<Result<Vec<_>>>()?;
+    let services = parser::merge_services(documents, &args.swagger, parse_options.keep_duplicate_names);
 
-    let services = parser::parse_swagger(&swagger_json, tag_filters)?;
+    if let Some(scope) = args.validate_unique_operation_names {
+        parser::validate_unique_function_names(&services, scope)?;
+    }
 
-    generator::write_services(&out_dir, &services, &args)?;
+    generator::write_services(&out_dir, &services, args)?;
 
-    println!("✓ Generated services in {}", out_dir.display());
+    if args.dry_run {
+        println!("Dry run: nothing written to {}", out_dir.display());
+    } else if !args.stdout {
+        println!("✓ Generated services in {}", out_dir.display());
+    }
     Ok(())
 }
+
+/// Parse a comma-separated tag list into normalized (lowercased) tag names
+fn tag_set(tags: &str) -> HashSet<String> {
+    tags.split(',')
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
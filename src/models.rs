@@ -1,15 +1,106 @@
+use anyhow::Result;
+use serde_json::Value;
 use std::collections::BTreeMap;
 
+use crate::cli::{ ArgsStyle, EmptyObject, EnumStyle, TypeStyle };
+use crate::formatting::{ block, jsdoc };
+use crate::naming::{ capitalize_first, property_key, to_pascal_identifier };
+use crate::template;
+
 /// Represents a single API operation (GET, POST, etc.)
 #[derive(Debug, Clone)]
 pub struct ApiOperation {
     pub path: String,
     pub method: String,
     pub function_name: String,
-    pub request_type: String,
+    /// Base path from the spec's `servers[0].url` or `basePath`, the default URL prefix
+    pub base_path: Option<String>,
+    /// `summary` of the operation, rendered as the first JSDoc line
+    pub summary: Option<String>,
+    /// `description` of the operation, rendered below the summary
+    pub description: Option<String>,
+    /// Type of the request body, `None` when the operation takes no body
+    pub body_type: Option<String>,
+    /// The body is marked `required: false`, or built from optional 2.0 form params
+    pub body_optional: bool,
+    /// Encoding of the request body
+    pub body_encoding: BodyEncoding,
+    /// Type of the query parameters object, `None` when there are no query parameters
+    pub query_type: Option<String>,
+    /// None of the query parameters is required
+    pub query_optional: bool,
     pub response_type: String,
-    #[allow(dead_code)]
+    /// Union of the documented error response types, with `--error-types`
+    pub error_type: Option<String>,
+    /// `operationId` of the spec, available to `--template` as `{{operationId}}`
     pub operation_id: Option<String>,
+    /// Path parameters in template order, passed as leading positional arguments
+    pub path_params: Vec<PathParam>,
+    /// Header parameters, passed as a trailing `headers` object argument
+    pub header_params: Vec<HeaderParam>,
+    /// Marked `x-long-running` or answered with `202 Accepted`, so the result has to be polled
+    pub long_running: bool,
+    /// Marked `deprecated: true`, documented with `@deprecated`
+    pub deprecated: bool,
+    /// Requires authentication: a non-empty `security` requirement, its own or the document's
+    pub secured: bool,
+    /// Descriptions of the arguments as (`@param` name, text) pairs in argument order, e.g.
+    /// `("userId", "Id of the user")` or `("params.page", "Page to return")`
+    pub param_docs: Vec<(String, String)>,
+    /// `description` of the response the function resolves to, documented with `@returns`
+    pub response_description: Option<String>,
+    /// Example payload of the success response, used for fixtures
+    pub response_example: Option<Example>,
+}
+
+/// A `{param}` segment of an operation path
+#[derive(Debug, Clone)]
+pub struct PathParam {
+    /// Name used in the path template
+    pub name: String,
+    /// Identifier of the generated function argument
+    pub arg_name: String,
+    pub field_type: String,
+}
+
+/// An `in: header` parameter, sent in the `headers` of the request
+#[derive(Debug, Clone)]
+pub struct HeaderParam {
+    /// Name of the HTTP header
+    pub name: String,
+    /// Key of the header in the generated `headers` argument
+    pub arg_name: String,
+    pub field_type: String,
+    pub optional: bool,
+}
+
+/// How the request body is encoded on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyEncoding {
+    #[default]
+    Json,
+    /// `application/x-www-form-urlencoded`, converted with the `toUrlEncoded` helper
+    FormUrlEncoded,
+    /// `multipart/form-data`, converted with the `toFormData` helper
+    Multipart,
+}
+
+impl BodyEncoding {
+    /// Media type of the request body
+    pub fn content_type(self) -> &'static str {
+        match self {
+            BodyEncoding::Json => "application/json",
+            BodyEncoding::FormUrlEncoded => "application/x-www-form-urlencoded",
+            BodyEncoding::Multipart => "multipart/form-data",
+        }
+    }
+}
+
+/// An example value taken from the spec, with the key it was declared under when named
+#[derive(Debug, Clone)]
+pub struct Example {
+    pub name: Option<String>,
+    pub value: Value,
 }
 
 /// Represents a service group (e.g., User, Order),
@@ -22,78 +113,613 @@ pub struct Service {
     pub type_definitions: BTreeMap<String, TypeDefinition>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FieldData {
     pub field_type: String,
     pub optional: bool,
-    #[allow(dead_code)]
+    /// `description` of the property, rendered as the first lines of the field JSDoc
     pub description: Option<String>,
+    /// Marked `readOnly`: set by the server and never sent in requests
+    pub read_only: bool,
+    /// Marked `writeOnly`: accepted in requests and never returned in responses
+    pub write_only: bool,
+    /// Validation constraints of the schema, documented on the field
+    pub constraints: Option<Constraints>,
+    /// Marked `deprecated: true`, documented with `@deprecated`
+    pub deprecated: bool,
+    /// JSON of the schema `default`, documented with `@default`
+    pub default: Option<String>,
+    /// Property name in the payload when the field was renamed by `--camel-fields`
+    pub wire_name: Option<String>,
+}
+
+impl FieldData {
+    /// JSDoc rendered above the field: its description, wire name, constraints, default and
+    /// deprecation, if any
+    pub fn doc_comment(&self) -> String {
+        let mut tags: Vec<String> = self.description.iter().cloned().collect();
+        tags.extend(self.wire_name.iter().map(|name| format!("Serialized as `{}`", name)));
+        tags.extend(self.constraints.as_ref().map(Constraints::tags).unwrap_or_default());
+        if let Some(default) = &self.default {
+            tags.push(format!("@default {}", default));
+        }
+        if self.deprecated {
+            tags.push("@deprecated".to_string());
+        }
+        jsdoc(&tags)
+    }
+}
+
+/// Numeric and string constraints of a field schema
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Constraints {
+    pub minimum: Option<String>,
+    pub maximum: Option<String>,
+    pub min_length: Option<String>,
+    pub max_length: Option<String>,
+    pub pattern: Option<String>,
+}
+
+impl Constraints {
+    /// JSDoc tags such as `@minimum 0`, in a fixed order
+    pub fn tags(&self) -> Vec<String> {
+        [
+            ("minimum", &self.minimum),
+            ("maximum", &self.maximum),
+            ("minLength", &self.min_length),
+            ("maxLength", &self.max_length),
+            ("pattern", &self.pattern),
+        ]
+            .into_iter()
+            .filter_map(|(tag, value)| value.as_ref().map(|value| format!("@{} {}", tag, value)))
+            .collect()
+    }
 }
 
 /// Represents a TypeScript type definition
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TypeDefinition {
     pub name: String,
     pub fields: BTreeMap<String, FieldData>,
     #[allow(dead_code)]
     pub description: Option<String>,
+    /// Type expression this definition aliases instead of an object body, e.g. a branded range
+    pub alias: Option<String>,
+    /// Members of a named enum schema, rendered as a TS `enum` with `EnumStyle::Enum`
+    pub enum_members: Vec<EnumMember>,
+    /// Value type of an open map described by `additionalProperties`, rendered as `Record<string, T>`
+    pub additional_properties: Option<String>,
+    /// Whether the schema is declared `type: object`, so that an empty one is still a map
+    pub is_object: bool,
+}
+
+/// A single value of an enum schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumMember {
+    /// Identifier of the member, from `x-enum-varnames` or derived from the value
+    pub name: String,
+    /// TypeScript literal of the value, e.g. `'ACTIVE'` or `1`
+    pub literal: String,
+}
+
+/// Options controlling how type definitions are rendered
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions {
+    pub enum_style: EnumStyle,
+    /// Pair literal union enums with a same-named `as const` object of their values
+    pub enum_consts: bool,
+    /// Declare object types as `type` aliases or `interface`s
+    pub type_style: TypeStyle,
+    /// Type of definitions without fields or `additionalProperties`
+    pub empty_object: EmptyObject,
+}
+
+/// Options controlling how request functions are rendered
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionOptions<'a> {
+    /// Prefix of the operation URL, e.g. `/api`
+    pub path_prefix: &'a str,
+    pub args_style: ArgsStyle,
+    /// Serialize the query params into the URL with the `toQueryString` helper
+    pub query_string: bool,
+    /// Generic type wrapping every response, e.g. `IResponse` for `IResponse<User>`
+    pub response_wrapper: Option<&'a str>,
+    /// Expression of the bearer token sent to secured operations, e.g. `getToken()`
+    pub auth_token: Option<&'a str>,
+    /// Text of the function with `{{placeholder}}`s, `template::FUNCTION_TEMPLATE` by default
+    pub template: &'a str,
+    /// Document the spec method and path of the operation, e.g. `GET /users/{id}`
+    pub source_comment: bool,
+    /// Take a trailing `signal?: AbortSignal` argument and pass it to the request
+    pub abort_signal: bool,
 }
 
 impl TypeDefinition {
-    pub fn to_typescript(&self) -> String {
-        if self.fields.is_empty() {
-            format!("export type {} = any;", self.name)
+    /// Create a definition that aliases an arbitrary type expression
+    pub fn alias(name: &str, target: String) -> Self {
+        TypeDefinition {
+            name: name.to_string(),
+            fields: BTreeMap::new(),
+            description: None,
+            alias: Some(target),
+            enum_members: Vec::new(),
+            additional_properties: None,
+            is_object: false,
+        }
+    }
+
+    pub fn to_typescript(&self, options: &RenderOptions) -> String {
+        if options.enum_style == EnumStyle::Enum && self.is_ts_enum_compatible() {
+            let members: Vec<String> = self.enum_members
+                .iter()
+                .map(|m| format!("{} = {},", m.name, m.literal))
+                .collect();
+            format!("export enum {} {}", self.name, block(&members))
+        } else if options.enum_consts && !self.enum_members.is_empty() {
+            let members: Vec<String> = self.enum_members
+                .iter()
+                .map(|m| format!("{}: {},", m.name, m.literal))
+                .collect();
+            format!(
+                "export const {0} = {1} as const;\n\nexport type {0} = (typeof {0})[keyof typeof {0}];",
+                self.name,
+                block(&members)
+            )
+        } else if let Some(alias) = &self.alias {
+            format!("export type {} = {};", self.name, alias)
+        } else if self.fields.is_empty() {
+            match self.record_type() {
+                Some(record) => format!("export type {} = {};", self.name, record),
+                None if self.is_object && options.empty_object == EmptyObject::Record => {
+                    format!("export type {} = Record<string, any>;", self.name)
+                }
+                None => format!("export type {} = any;", self.name),
+            }
         } else {
-            let mut body = String::from("{\n");
-            for (field_name, field_data) in &self.fields {
-                let optional = if field_data.optional { "?" } else { "" };
-                body.push_str(
-                    &format!("  {}{}: {};\n", field_name, optional, field_data.field_type)
-                );
+            let members: Vec<String> = self.fields
+                .iter()
+                .map(|(field_name, field_data)| {
+                    let optional = if field_data.optional { "?" } else { "" };
+                    let doc = field_data.doc_comment();
+                    let separator = if doc.is_empty() { "" } else { "\n" };
+                    format!(
+                        "{}{}{}{}: {};",
+                        doc,
+                        separator,
+                        property_key(field_name),
+                        optional,
+                        field_data.field_type
+                    )
+                })
+                .collect();
+            match self.record_type() {
+                Some(record) => format!("export type {} = {} & {};", self.name, block(&members), record),
+                // Open maps keep the alias form, an interface cannot intersect with `Record`
+                None if options.type_style == TypeStyle::Interface => {
+                    format!("export interface {} {}", self.name, block(&members))
+                }
+                None => format!("export type {} = {}", self.name, block(&members)),
             }
-            body.push('}');
-            format!("export type {} = {}", self.name, body)
+        }
+    }
+
+    /// Whether the declaration is a TS enum or const object whose members are accessed by name
+    pub fn has_named_members(&self, options: &RenderOptions) -> bool {
+        (options.enum_style == EnumStyle::Enum && self.is_ts_enum_compatible()) ||
+            (options.enum_consts && !self.enum_members.is_empty())
+    }
+
+    /// Names of the `readOnly` fields, in field order
+    pub fn read_only_fields(&self) -> Vec<&str> {
+        self.fields
+            .iter()
+            .filter(|(_, field)| field.read_only)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Names of the `writeOnly` fields, in field order
+    pub fn write_only_fields(&self) -> Vec<&str> {
+        self.fields
+            .iter()
+            .filter(|(_, field)| field.write_only)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// `Record<string, T>` for the open map part of the type, if any
+    fn record_type(&self) -> Option<String> {
+        self.additional_properties.as_ref().map(|value| format!("Record<string, {}>", value))
+    }
+
+    /// TS enums only accept string and number members
+    fn is_ts_enum_compatible(&self) -> bool {
+        !self.enum_members.is_empty() &&
+            self.enum_members
+                .iter()
+                .all(|m| m.literal.starts_with('\'') || m.literal.parse::<f64>().is_ok())
+    }
+
+    /// Render the type as a single-line object literal for use at a reference site,
+    /// e.g. `{ id: number; name?: string }`
+    pub fn to_inline_typescript(&self) -> String {
+        if self.fields.is_empty() {
+            return self.record_type().unwrap_or_else(|| String::from("any"));
+        }
+        let members: Vec<String> = self.fields
+            .iter()
+            .map(|(field_name, field_data)| {
+                let optional = if field_data.optional { "?" } else { "" };
+                format!("{}{}: {}", property_key(field_name), optional, field_data.field_type)
+            })
+            .collect();
+        match self.record_type() {
+            Some(record) => format!("{{ {} }} & {}", members.join("; "), record),
+            None => format!("{{ {} }}", members.join("; ")),
         }
     }
 }
 
 impl ApiOperation {
-    pub fn to_typescript_function(&self, path_prefix: &str) -> String {
-        let arg_name = match self.method.as_str() {
-            "GET" | "DELETE" => "params",
-            _ => "data",
+    /// Arguments of the generated function as (name, type) pairs: path params first,
+    /// then `params` for the query object, `data` for the body and `headers` for the header
+    /// params, omitting empty groups
+    pub fn arguments(&self) -> Vec<(String, String)> {
+        let mut args: Vec<(String, String)> = self.path_params
+            .iter()
+            .map(|p| (p.arg_name.clone(), p.field_type.clone()))
+            .collect();
+        if let Some(query_type) = &self.query_type {
+            args.push(("params".to_string(), query_type.clone()));
+        }
+        if let Some(body_type) = &self.body_type {
+            args.push(("data".to_string(), body_type.clone()));
+        }
+        if let Some(headers_type) = self.headers_type() {
+            args.push(("headers".to_string(), headers_type));
+        }
+        args
+    }
+
+    /// Declared parameters as (name, type) pairs, with `?` marking an optional `params` or
+    /// `data`, e.g. `data?`. A positional parameter is only optional when the ones after it
+    /// are as well.
+    pub fn parameters(&self, args_style: ArgsStyle) -> Vec<(String, String)> {
+        let optional = |name: &str| match name {
+            "params" => self.query_optional,
+            "data" => self.body_optional,
+            _ => false,
         };
+        let mut trailing_optional = true;
+        let mut parameters: Vec<(String, String)> = self.arguments()
+            .into_iter()
+            .rev()
+            .map(|(name, ts_type)| {
+                trailing_optional &= optional(&name);
+                let is_optional = match args_style {
+                    ArgsStyle::Positional => trailing_optional,
+                    ArgsStyle::Object => optional(&name),
+                };
+                (if is_optional { format!("{}?", name) } else { name }, ts_type)
+            })
+            .collect();
+        parameters.reverse();
+        parameters
+    }
+
+    /// `parameters` of the generated function itself, followed by `signal?` with `abort_signal`
+    fn function_parameters(&self, args_style: ArgsStyle, abort_signal: bool) -> Vec<(String, String)> {
+        let mut parameters = self.parameters(args_style);
+        if abort_signal {
+            parameters.push(("signal?".to_string(), "AbortSignal".to_string()));
+        }
+        parameters
+    }
+
+    /// Whether every argument can be left out, e.g. only an all-optional query object
+    pub fn all_arguments_optional(&self) -> bool {
+        self.parameters(ArgsStyle::Positional).iter().all(|(name, _)| name.ends_with('?'))
+    }
+
+    /// Type of the `headers` argument, e.g. `{ xTenant: string; xTrace?: string }`,
+    /// `None` without header params
+    pub fn headers_type(&self) -> Option<String> {
+        if self.header_params.is_empty() {
+            return None;
+        }
+        let members: Vec<String> = self.header_params
+            .iter()
+            .map(|p| format!("{}{}: {}", p.arg_name, if p.optional { "?" } else { "" }, p.field_type))
+            .collect();
+        Some(format!("{{ {} }}", members.join("; ")))
+    }
+
+    /// Type of the payload passed to `request`: the body, else the query object
+    pub fn payload_type(&self) -> String {
+        self.body_type.clone().or_else(|| self.query_type.clone()).unwrap_or_else(|| "any".to_string())
+    }
 
-        let req_type = if self.request_type.is_empty() || self.request_type == "any" {
+    pub fn response_ts_type(&self) -> String {
+        if self.response_type.is_empty() || self.response_type == "any" {
             "any".to_string()
         } else {
-            format!("{}", self.request_type)
-        };
+            self.response_type.clone()
+        }
+    }
 
-        let resp_type = if self.response_type.is_empty() || self.response_type == "any" {
-            "any".to_string()
+    /// Whether the success response has no body (`204 No Content`)
+    pub fn returns_void(&self) -> bool {
+        self.response_type == "void"
+    }
+
+    /// Type expressions this operation refers to: body, query, path params and response
+    pub fn referenced_types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = self.path_params.iter().map(|p| p.field_type.as_str()).collect();
+        types.extend(self.query_type.as_deref());
+        types.extend(self.body_type.as_deref());
+        types.push(&self.response_type);
+        types.extend(self.error_type.as_deref());
+        types
+    }
+
+    /// JSDoc block from the summary and description, followed by the `GET /users/{id}` source
+    /// line with `source_comment`, or an empty string when there is nothing to say
+    pub fn doc_comment(&self, source_comment: bool) -> String {
+        let summary = self.summary.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let description = self.description
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty() && Some(*d) != summary);
+        let mut lines: Vec<String> = summary.map(String::from).into_iter().collect();
+        if let Some(description) = description {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(description.to_string());
+        }
+        if source_comment {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(format!("{} {}", self.method, self.path));
+        }
+        for (name, description) in &self.param_docs {
+            lines.push(format!("@param {} {}", name, description));
+        }
+        if let Some(description) = &self.response_description {
+            lines.push(format!("@returns {}", description));
+        }
+        if self.error_type.is_some() {
+            lines.push(format!("@throws {{{}}}", self.error_type_name()));
+        }
+        if self.deprecated {
+            lines.push("@deprecated".to_string());
+        }
+        jsdoc(&lines)
+    }
+
+    /// Name of the per-operation argument object type used with `ArgsStyle::Object`
+    pub fn args_type_name(&self) -> String {
+        format!("{}Args", capitalize_first(&self.function_name))
+    }
+
+    /// Name of the per-operation error type declared with `--error-types`
+    pub fn error_type_name(&self) -> String {
+        format!("{}Error", capitalize_first(&self.function_name))
+    }
+
+    /// Parameter list of the generated function: positional arguments, or a single
+    /// destructured `{ id, params, data }: XxxArgs` object
+    pub fn parameter_list(&self, args_style: ArgsStyle, abort_signal: bool) -> String {
+        match args_style {
+            ArgsStyle::Positional => self.signature(abort_signal),
+            ArgsStyle::Object => {
+                let names: Vec<String> = self.function_parameters(args_style, abort_signal)
+                    .into_iter()
+                    .map(|(name, _)| name.trim_end_matches('?').to_string())
+                    .collect();
+                // The whole object can be left out when every member is optional
+                if names.is_empty() {
+                    String::new()
+                } else if self.all_arguments_optional() {
+                    format!("{{ {} }}: {} = {{}}", names.join(", "), self.args_type_name())
+                } else {
+                    format!("{{ {} }}: {}", names.join(", "), self.args_type_name())
+                }
+            }
+        }
+    }
+
+    /// `export type XxxArgs = { ... };` for `ArgsStyle::Object`, `None` without arguments
+    fn args_type(&self, abort_signal: bool) -> Option<String> {
+        let members: Vec<String> = self.function_parameters(ArgsStyle::Object, abort_signal)
+            .into_iter()
+            .map(|(name, ts_type)| format!("{}: {};", name, ts_type))
+            .collect();
+        if members.is_empty() {
+            return None;
+        }
+        Some(format!("export type {} = {};", self.args_type_name(), block(&members)))
+    }
+
+    /// Type the generated function resolves to, wrapped in the `--response-wrapper` if any
+    pub fn return_type(&self, options: &FunctionOptions) -> String {
+        match options.response_wrapper {
+            Some(wrapper) => format!("{}<{}>", wrapper, self.response_ts_type()),
+            None => self.response_ts_type(),
+        }
+    }
+
+    /// URL expression of the request. With `query_string`, the query params are serialized
+    /// into it by the `toQueryString` helper instead of being passed separately.
+    pub fn request_url(&self, options: &FunctionOptions) -> String {
+        let url = self.url_expression(options.path_prefix);
+        if self.query_type.is_some() && options.query_string {
+            // Turn the plain or template literal URL into a template literal with the query appended
+            let path = url.trim_matches(|c| c == '\'' || c == '`');
+            format!("`{}${{toQueryString(params)}}`", path)
         } else {
-            format!("{}", self.response_type)
-        };
+            url
+        }
+    }
+
+    /// Expression of the request body sent by the generated function: `data`, converted to
+    /// `URLSearchParams` or `FormData` for form bodies, which carry their own content type
+    pub fn body_expression(&self) -> &'static str {
+        match self.body_encoding {
+            BodyEncoding::Json => "data",
+            BodyEncoding::FormUrlEncoded => "toUrlEncoded(data)",
+            BodyEncoding::Multipart => "toFormData(data)",
+        }
+    }
+
+    /// `'X-Tenant': headers.xTenant` entries mapping header names to the `headers` argument,
+    /// preceded by the bearer `Authorization` header of secured operations with `auth_token`
+    pub fn header_entries(&self, options: &FunctionOptions) -> Vec<String> {
+        let mut entries = Vec::new();
+        if let Some(token) = options.auth_token.filter(|_| self.secured) {
+            entries.push(format!("'Authorization': `Bearer ${{{}}}`", token));
+        }
+        entries.extend(self.header_params.iter().map(|p| format!("'{}': headers.{}", p.name, p.arg_name)));
+        entries
+    }
+
+    /// Object literal of the `header_entries`, `None` when there are none
+    pub fn headers_expression(&self, options: &FunctionOptions) -> Option<String> {
+        let entries = self.header_entries(options);
+        if entries.is_empty() { None } else { Some(format!("{{ {} }}", entries.join(", "))) }
+    }
+
+    /// Render the exported request function around the `body` statements produced for the
+    /// HTTP client in use
+    pub fn to_typescript_function(&self, options: &FunctionOptions, body: &[String]) -> String {
+        let mut declarations = self.declarations(options);
+        let doc = self.doc_comment(options.source_comment);
+        let values = [
+            ("doc", if doc.is_empty() { doc } else { format!("{}\n", doc) }),
+            ("name", self.function_name.clone()),
+            ("params", self.parameter_list(options.args_style, options.abort_signal)),
+            ("returnType", self.return_type(options)),
+            ("body", block(body)),
+            ("method", self.method.clone()),
+            ("path", self.path.clone()),
+            ("url", self.request_url(options)),
+            ("operationId", self.operation_id.clone().unwrap_or_default()),
+            ("summary", self.summary.clone().unwrap_or_default()),
+        ];
+        declarations.push(template::render(options.template, &values));
+        declarations.join("\n\n")
+    }
 
-        let url = format!(
+    /// Module-level types declared along with the function: its `XxxArgs` with
+    /// `ArgsStyle::Object` and its `XxxError` with `--error-types`
+    pub fn declarations(&self, options: &FunctionOptions) -> Vec<String> {
+        let mut declarations: Vec<String> = Vec::new();
+        if options.args_style == ArgsStyle::Object {
+            declarations.extend(self.args_type(options.abort_signal));
+        }
+        if let Some(error_type) = &self.error_type {
+            declarations.push(format!("export type {} = {};", self.error_type_name(), error_type));
+        }
+        declarations
+    }
+
+    /// Typed argument list of the generated function, e.g. `id: number, params: Query`
+    fn signature(&self, abort_signal: bool) -> String {
+        self.function_parameters(ArgsStyle::Positional, abort_signal)
+            .iter()
+            .map(|(name, ts_type)| format!("{}: {}", name, ts_type))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// URL of the operation as a TS expression: a quoted string, or a template literal
+    /// interpolating the path param arguments, e.g. `` `/users/${id}` ``
+    fn url_expression(&self, path_prefix: &str) -> String {
+        let mut url = format!(
             "{}/{}",
             path_prefix.trim_end_matches('/'),
             self.path.trim_start_matches('/')
         );
+        if self.path_params.is_empty() {
+            return format!("'{}'", url);
+        }
+        for param in &self.path_params {
+            url = url.replace(&format!("{{{}}}", param.name), &format!("${{{}}}", param.arg_name));
+        }
+        format!("`{}`", url)
+    }
+
+    /// Render the response example as an exported fixture constant,
+    /// e.g. `getUserActiveUserExample` for an example keyed `activeUser`
+    pub fn to_fixture(&self) -> Option<String> {
+        let example = self.response_example.as_ref()?;
+        let key = example.name.as_deref().map(to_pascal_identifier).unwrap_or_default();
+        let value = serde_json::to_string_pretty(&example.value).ok()?;
+        Some(format!("export const {}{}Example = {};", self.function_name, key, value))
+    }
+
+    /// Render a `pollXxx` helper that starts the operation and polls the status endpoint
+    /// described by `PollOptions` until it reports completion. `status_request` renders the
+    /// statements fetching the status at a URL expression into `status`, per HTTP client.
+    pub fn to_polling_helper(
+        &self,
+        args_style: ArgsStyle,
+        status_request: &dyn Fn(&str) -> Result<Vec<String>>
+    ) -> Result<String> {
+        // A required `options` cannot follow optional positional arguments, so these take
+        // `undefined` explicitly
+        let signature = match args_style {
+            ArgsStyle::Positional => self.parameters(args_style)
+                .into_iter()
+                .map(|(name, ts_type)| match name.strip_suffix('?') {
+                    Some(name) => format!("{}: {} | undefined", name, ts_type),
+                    None => format!("{}: {}", name, ts_type),
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+            ArgsStyle::Object => self.parameter_list(args_style, false),
+        };
+        let params = if signature.is_empty() {
+            "options: PollOptions<T>".to_string()
+        } else {
+            format!("{}, options: PollOptions<T>", signature)
+        };
+        let call_args: Vec<String> = self.arguments()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        let call_args = match args_style {
+            ArgsStyle::Object if !call_args.is_empty() => vec![format!("{{ {} }}", call_args.join(", "))],
+            _ => call_args,
+        };
+        let mut loop_body = status_request("options.statusUrl(initial)")?;
+        loop_body.push(
+            format!(
+                "if (options.isDone(status)) {}",
+                block(&["return options.result ? options.result(status) : status;".to_string()])
+            )
+        );
+        loop_body.push("await new Promise((resolve) => setTimeout(resolve, options.intervalMs ?? 1000));".to_string());
+        let loop_body = block(&loop_body);
+        let body = block(
+            &[
+                format!("const initial = await {}({});", self.function_name, call_args.join(", ")),
+                format!(
+                    "for (let attempt = 0; attempt < (options.maxAttempts ?? 60); attempt++) {}",
+                    loop_body
+                ),
+                format!("throw new Error('{} did not complete in time');", self.function_name),
+            ]
+        );
 
-        format!(
-            "export const {} = async ({}: {}): Promise<{}> => {{\n  return request<{}, {}>({{\n    url: '{}',\n    {}: {},\n    method: '{}',\n  }});\n}};",
-            self.function_name,
-            arg_name,
-            req_type,
-            resp_type,
-            req_type,
-            resp_type,
-            url,
-            arg_name,
-            arg_name,
-            self.method
+        Ok(
+            format!(
+                "export const poll{} = async <T = any>({}): Promise<T> => {};",
+                capitalize_first(&self.function_name),
+                params,
+                body
+            )
         )
     }
 }
@@ -0,0 +1,101 @@
+//! Helpers turning spec names and free text into TypeScript identifiers
+
+/// Capitalize first character of string
+pub fn capitalize_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => {
+            let mut result = String::new();
+            result.push_str(&first.to_uppercase().collect::<String>());
+            result.push_str(chars.as_str());
+            result
+        }
+    }
+}
+
+/// Turn free text such as a summary into a camelCase identifier, e.g. "Get all users" -> "getAllUsers"
+pub fn to_camel_identifier(text: &str) -> String {
+    let mut result = String::new();
+    for word in text.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()) {
+        if result.is_empty() {
+            // Keep inner capitals of camelCase words ("userId"), but lower acronyms ("ID")
+            if word.chars().all(|c| !c.is_ascii_lowercase()) {
+                result.push_str(&word.to_lowercase());
+            } else {
+                let mut chars = word.chars();
+                result.extend(chars.next().map(|c| c.to_ascii_lowercase()));
+                result.push_str(chars.as_str());
+            }
+        } else {
+            result.push_str(&capitalize_first(word));
+        }
+    }
+    if result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    result
+}
+
+/// camelCase a snake_case or kebab-case property name, e.g. "created_at" -> "createdAt".
+/// Names without separators, camelCase ones included, and leading underscores are kept.
+pub fn to_camel_case(name: &str) -> String {
+    let body = name.trim_start_matches('_');
+    if !body.contains(['_', '-']) {
+        return name.to_string();
+    }
+    let mut result = name[..name.len() - body.len()].to_string();
+    for (idx, word) in body.split(['_', '-']).filter(|w| !w.is_empty()).enumerate() {
+        // SCREAMING_SNAKE words are lowered, camelCase ones keep their inner capitals
+        let word = if word.chars().any(|c| c.is_lowercase()) { word.to_string() } else { word.to_lowercase() };
+        if idx == 0 {
+            let mut chars = word.chars();
+            result.extend(chars.next().map(|c| c.to_ascii_lowercase()));
+            result.push_str(chars.as_str());
+        } else {
+            result.push_str(&capitalize_first(&word));
+        }
+    }
+    result
+}
+
+/// Turn free text into a PascalCase identifier, e.g. "active user" -> "ActiveUser"
+pub fn to_pascal_identifier(text: &str) -> String {
+    let camel = to_camel_identifier(text);
+    if camel.starts_with('_') { camel } else { capitalize_first(&camel) }
+}
+
+/// Turn an identifier or free text into kebab-case, e.g. "UserAPIController" -> "user-api-controller"
+pub fn to_kebab_case(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::new();
+    for (idx, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !result.is_empty() && !result.ends_with('-') {
+                result.push('-');
+            }
+            continue;
+        }
+        // A capital starts a word after a lowercase letter or digit, or ends an acronym ("APIKey")
+        if c.is_uppercase() && idx > 0 && !result.ends_with('-') {
+            let prev = chars[idx - 1];
+            let next_is_lower = chars.get(idx + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower) {
+                result.push('-');
+            }
+        }
+        result.extend(c.to_lowercase());
+    }
+    result.trim_end_matches('-').to_string()
+}
+
+/// Check if a name can be used as a bare JS identifier
+pub fn is_identifier(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$') &&
+        name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Object key for a property name, quoted when it is not a valid identifier
+pub fn property_key(name: &str) -> String {
+    if is_identifier(name) { name.to_string() } else { format!("'{}'", name.replace('\'', "\\'")) }
+}
@@ -1,17 +1,47 @@
+mod external_refs;
+
+pub use external_refs::referenced_files;
+
 use anyhow::{ Context, Result };
+use serde::de::{ Deserialize, Deserializer, IgnoredAny, MapAccess, Visitor };
 use serde_json::Value;
-use std::collections::{ BTreeMap, HashSet };
+use std::borrow::Cow;
+use std::cell::{ Cell, RefCell };
+use std::collections::{ BTreeMap, HashMap, HashSet };
 use std::fs;
+use std::io::{ BufRead, BufReader, Read };
+use std::path::Path;
 
-use crate::models::{ ApiOperation, FieldData, Service, TypeDefinition };
+use crate::cli::{ DateType, Int64Type, NameScope, OperationNameSource };
+use crate::naming::{
+    capitalize_first,
+    is_identifier,
+    property_key,
+    to_camel_case,
+    to_camel_identifier,
+    to_pascal_identifier,
+};
+use crate::models::{
+    ApiOperation,
+    BodyEncoding,
+    Constraints,
+    EnumMember,
+    Example,
+    FieldData,
+    HeaderParam,
+    PathParam,
+    Service,
+    TypeDefinition,
+};
+use crate::type_expr::split_top_level;
 
-/// Read and parse swagger JSON file (supports local file and remote URL)
-pub fn read_swagger_file(path: &str) -> Result<Value> {
-    let content = if path.starts_with("http://") || path.starts_with("https://") {
-        // Fetch from remote URL using synchronous HTTP request
-        ureq::get(path)
-            .call()
-            .with_context(|| format!("Failed to fetch swagger from URL: {}", path))?
+/// Read and parse a swagger JSON or YAML file (supports local file and remote URL).
+/// `headers` are `Name: value` pairs sent along with remote requests, e.g. an auth token.
+/// With `pretty_errors`, syntax errors point at the offending line and column with a snippet.
+/// With `verbose`, the files read to resolve `$ref`s into other files are reported.
+pub fn read_swagger_file(path: &str, headers: &[String], pretty_errors: bool, verbose: bool) -> Result<Value> {
+    let content = if is_remote(path) {
+        fetch_remote(path, headers)?
             .into_string()
             .with_context(|| format!("Failed to read response from URL: {}", path))?
     } else {
@@ -20,105 +50,676 @@ pub fn read_swagger_file(path: &str) -> Result<Value> {
             .with_context(|| format!("Failed to read swagger file: {}", path))?
     };
 
-    serde_json::from_str(&content).context("Invalid JSON in swagger file")
+    let mut spec = if looks_like_json(content.as_bytes()) {
+        match serde_json::from_str(&content) {
+            Ok(value) => value,
+            Err(err) if pretty_errors => return Err(anyhow::anyhow!(describe_json_error(path, Some(&content), &err))),
+            Err(err) => return Err(err).context("Invalid JSON in swagger file"),
+        }
+    } else {
+        parse_yaml_or_json(path, &content)?
+    };
+    if !is_remote(path) {
+        external_refs::inline_external_refs(&mut spec, Path::new(path), verbose)?;
+    }
+    Ok(spec)
+}
+
+/// Read swagger JSON incrementally from the file or response stream, without buffering the
+/// raw text, and drop top-level sections that never influence the output while parsing.
+/// Produces the same services as `read_swagger_file`. Only the raw text is saved: `paths`
+/// and `components` are still parsed whole. YAML specs are read whole.
+pub fn read_swagger_streaming(path: &str, headers: &[String], pretty_errors: bool, verbose: bool) -> Result<Value> {
+    let reader: Box<dyn Read> = if is_remote(path) {
+        fetch_remote(path, headers)?.into_reader()
+    } else {
+        let file = fs::File
+            ::open(path)
+            .with_context(|| format!("Failed to read swagger file: {}", path))?;
+        Box::new(file)
+    };
+
+    let mut reader = BufReader::new(reader);
+    // Only the first buffered chunk is sniffed, enough to skip leading whitespace
+    let head = reader.fill_buf().with_context(|| format!("Failed to read swagger file: {}", path))?;
+    let mut spec = if looks_like_json(head) {
+        match serde_json::from_reader::<_, RelevantSections>(reader) {
+            Ok(document) => document.0,
+            Err(err) if pretty_errors => return Err(anyhow::anyhow!(describe_json_error(path, None, &err))),
+            Err(err) => return Err(err).context("Invalid JSON in swagger file"),
+        }
+    } else {
+        let mut content = String::new();
+        reader
+            .read_to_string(&mut content)
+            .with_context(|| format!("Failed to read swagger file: {}", path))?;
+        parse_yaml_or_json(path, &content)?
+    };
+    if !is_remote(path) {
+        external_refs::inline_external_refs(&mut spec, Path::new(path), verbose)?;
+    }
+    Ok(spec)
+}
+
+/// Sniff the format of a spec from its first non-whitespace character, whatever its name:
+/// JSON documents start with `{` or `[`, anything else is read as YAML
+fn looks_like_json(content: &[u8]) -> bool {
+    matches!(content.iter().find(|b| !b.is_ascii_whitespace()), Some(b'{' | b'[') | None)
+}
+
+/// Parse a document that does not look like JSON, as YAML and then as JSON, failing with
+/// both errors when neither parses
+fn parse_yaml_or_json(path: &str, content: &str) -> Result<Value> {
+    let yaml_err = match serde_yaml::from_str::<serde_yaml::Value>(content) {
+        Ok(value) => return yaml_to_json(value).with_context(|| format!("Unsupported YAML in {}", path)),
+        Err(err) => err,
+    };
+    serde_json::from_str(content).map_err(|json_err| {
+        anyhow::anyhow!("{} is neither YAML nor JSON\n  YAML: {}\n  JSON: {}", path, yaml_err, json_err)
+    })
+}
+
+/// Convert a YAML document to JSON. Mapping keys that are not strings, such as unquoted
+/// response codes (`200:`), become their string form; tags are dropped.
+fn yaml_to_json(value: serde_yaml::Value) -> Result<Value> {
+    use serde_yaml::Value as Yaml;
+    Ok(match value {
+        Yaml::Null => Value::Null,
+        Yaml::Bool(b) => Value::Bool(b),
+        Yaml::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::from(i)
+            } else if let Some(u) = n.as_u64() {
+                Value::from(u)
+            } else {
+                let f = n.as_f64().unwrap_or(f64::NAN);
+                match serde_json::Number::from_f64(f) {
+                    Some(number) => Value::Number(number),
+                    None => anyhow::bail!("{} cannot be represented in JSON", f),
+                }
+            }
+        }
+        Yaml::String(s) => Value::String(s),
+        Yaml::Sequence(items) => Value::Array(items.into_iter().map(yaml_to_json).collect::<Result<_>>()?),
+        Yaml::Mapping(mapping) => {
+            let mut object = serde_json::Map::new();
+            for (key, value) in mapping {
+                let key = match key {
+                    Yaml::String(s) => s,
+                    Yaml::Number(n) => n.to_string(),
+                    Yaml::Bool(b) => b.to_string(),
+                    Yaml::Null => "null".to_string(),
+                    other => anyhow::bail!("Unsupported mapping key: {:?}", other),
+                };
+                object.insert(key, yaml_to_json(value)?);
+            }
+            Value::Object(object)
+        }
+        Yaml::Tagged(tagged) => yaml_to_json(tagged.value)?,
+    })
+}
+
+/// Describe a JSON syntax error with the file name, line and column, and when the source
+/// text is available, the offending line with a caret under the error position
+fn describe_json_error(path: &str, content: Option<&str>, err: &serde_json::Error) -> String {
+    let message = err.to_string();
+    // serde_json appends " at line X column Y" to its messages, which we render ourselves
+    let message = message.split(" at line ").next().unwrap_or(&message);
+    let mut description = format!(
+        "Invalid JSON in swagger file {}:{}:{}: {}",
+        path,
+        err.line(),
+        err.column(),
+        message
+    );
+
+    if let Some(line) = content.and_then(|c| c.lines().nth(err.line().saturating_sub(1))) {
+        let line_no = err.line().to_string();
+        let gutter = " ".repeat(line_no.len());
+        let caret_offset = line
+            .chars()
+            .take(err.column().saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        description.push_str(&format!("\n {} |\n {} | {}\n {} | {}^", gutter, line_no, line, gutter, caret_offset));
+    }
+    description
+}
+
+/// Top-level sections that are skipped by the streaming reader
+const UNUSED_SECTIONS: [&str; 3] = ["info", "tags", "externalDocs"];
+
+/// A swagger document deserialized without materializing its unused top-level sections; the
+/// other sections are kept whole
+struct RelevantSections(Value);
+
+impl<'de> Deserialize<'de> for RelevantSections {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SectionsVisitor;
+
+        impl<'de> Visitor<'de> for SectionsVisitor {
+            type Value = RelevantSections;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a swagger/openapi object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut sections = serde_json::Map::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    if UNUSED_SECTIONS.contains(&key.as_str()) || key.starts_with("x-") {
+                        map.next_value::<IgnoredAny>()?;
+                    } else {
+                        sections.insert(key, map.next_value()?);
+                    }
+                }
+                Ok(RelevantSections(Value::Object(sections)))
+            }
+        }
+
+        deserializer.deserialize_map(SectionsVisitor)
+    }
+}
+
+pub fn is_remote(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// Fetch a document from a remote URL using a synchronous HTTP request
+fn fetch_remote(url: &str, headers: &[String]) -> Result<ureq::Response> {
+    let mut request = ureq::get(url);
+    for header in headers {
+        let (name, value) = header
+            .split_once(':')
+            .with_context(|| format!("Invalid header '{}', expected 'Name: value'", header))?;
+        request = request.set(name.trim(), value.trim());
+    }
+
+    match request.call() {
+        Ok(response) => Ok(response),
+        Err(ureq::Error::Status(code, response)) => {
+            anyhow::bail!(
+                "Failed to fetch swagger from URL: {} (HTTP {} {})",
+                url,
+                code,
+                response.status_text()
+            );
+        }
+        Err(err) => {
+            Err(err).with_context(|| format!("Failed to fetch swagger from URL: {}", url))
+        }
+    }
+}
+
+/// Options controlling which operations are parsed and how their types are resolved
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Normalized tags to generate; all tags are generated when `None`
+    pub tag_filters: Option<HashSet<String>>,
+    /// Normalized tags whose operations are skipped, applied after `tag_filters`
+    pub excluded_tags: HashSet<String>,
+    /// Object types with fewer fields than this are inlined at their reference site
+    pub inline_small_types: Option<usize>,
+    /// Map numbers with range constraints to branded range aliases
+    pub branded_ranges: bool,
+    /// Source used to derive function names
+    pub operation_name_from: OperationNameSource,
+    /// TypeScript type used for `format: date-time` / `date` strings
+    pub date_type: DateType,
+    /// TypeScript type used for `format: int64` integers
+    pub int64: Int64Type,
+    /// Keep colliding function names as-is so they can be validated instead of suffixed
+    pub keep_duplicate_names: bool,
+    /// Emit operations into the service of every (matching) tag instead of only the first
+    pub multi_tag: bool,
+    /// Wrap request body types in `Omit<T, ...>` of their `readOnly` fields
+    pub omit_readonly_in_request: bool,
+    /// Wrap response types in `Omit<T, ...>` of their `writeOnly` fields
+    pub omit_writeonly_in_response: bool,
+    /// camelCase the property names of object schemas, keeping the wire name on the field
+    pub camel_fields: bool,
+    /// Type the response as the union of every 2xx response instead of the preferred one
+    pub union_success_responses: bool,
+    /// Collect the typed error responses of each operation into `ApiOperation::error_type`
+    pub error_types: bool,
+    /// Name services after the PascalCased tag instead of the lowercased one
+    pub preserve_tag_case: bool,
+}
+
+/// Shared state used while resolving schemas into TypeScript types
+struct SchemaContext<'a> {
+    /// The whole spec, against which local `$ref`s outside the schemas are resolved
+    document: &'a Value,
+    schemas: Option<&'a Value>,
+    options: &'a ParseOptions,
+    /// `$ref` targets currently being expanded inline, used to stop on cycles
+    expanding: RefCell<Vec<String>>,
+    /// Aliases synthesized while resolving types (e.g. branded ranges), keyed by name
+    aliases: RefCell<BTreeMap<String, TypeDefinition>>,
+    /// Nesting level of the inline object currently being expanded
+    inline_depth: Cell<usize>,
+    /// Memoized TS type of each `$ref`ed schema name
+    ref_types: RefCell<HashMap<String, String>>,
+    /// Memoized type definition of each named schema
+    definitions: RefCell<HashMap<String, TypeDefinition>>,
+}
+
+impl<'a> SchemaContext<'a> {
+    fn new(document: &'a Value, schemas: Option<&'a Value>, options: &'a ParseOptions) -> Self {
+        SchemaContext {
+            document,
+            schemas,
+            options,
+            expanding: RefCell::new(Vec::new()),
+            aliases: RefCell::new(BTreeMap::new()),
+            inline_depth: Cell::new(0),
+            ref_types: RefCell::new(HashMap::new()),
+            definitions: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Follow a local `$ref` such as `#/components/parameters/PageSize` (3.0) or
+    /// `#/parameters/PageSize` (2.0) to its target, through chains of references.
+    /// Values that are not references are returned as is; unresolvable ones are reported.
+    fn resolve_ref<'v>(&self, value: &'v Value, kind: &str) -> Option<&'v Value>
+        where 'a: 'v
+    {
+        let mut resolved = value;
+        // Bounded to stop on cycles
+        for _ in 0..8 {
+            let Some(ref_str) = resolved.get("$ref").and_then(|v| v.as_str()) else {
+                return Some(resolved);
+            };
+            let Some(target) = ref_str.strip_prefix('#').and_then(|pointer| self.document.pointer(pointer)) else {
+                eprintln!("  ⚠ Skipping {}: cannot resolve $ref '{}'", kind, ref_str);
+                return None;
+            };
+            resolved = target;
+        }
+        eprintln!("  ⚠ Skipping {}: $refs are nested too deeply or circular", kind);
+        None
+    }
+
+    /// Whether resolution is outside any inline expansion. Only such results are memoized,
+    /// since results inside an expansion can be cut short by cycle and depth guards.
+    fn is_top_level(&self) -> bool {
+        self.expanding.borrow().is_empty() && self.inline_depth.get() == 0
+    }
+
+    /// Look up a named schema in the schemas root
+    fn schema(&self, name: &str) -> Option<&'a Value> {
+        self.schemas.and_then(|s| s.get(name))
+    }
+
+    /// Whether an object type with `field_count` fields is small enough to be inlined
+    fn should_inline(&self, field_count: usize) -> bool {
+        match self.options.inline_small_types {
+            Some(threshold) => field_count > 0 && field_count < threshold,
+            None => false,
+        }
+    }
 }
 
 /// Parse swagger JSON into organized services
-pub fn parse_swagger(
-    swagger: &Value,
-    tag_filters: Option<HashSet<String>>
-) -> Result<Vec<Service>> {
+pub fn parse_swagger(swagger: &Value, options: &ParseOptions) -> Result<Vec<Service>> {
     let paths = swagger
         .get("paths")
         .and_then(|p| p.as_object())
         .context("No 'paths' found in swagger file")?;
 
-    let schemas_root = find_schemas(&swagger);
+    let schemas_root = find_schemas(swagger);
+    let ctx = SchemaContext::new(swagger, schemas_root, options);
 
+    let base_path = extract_base_path(swagger);
     let mut service_map: BTreeMap<String, Service> = BTreeMap::new();
 
     // Group operations by tag
-    for (path, path_item) in paths.iter() {
+    for (raw_path, path_item) in paths.iter() {
+        let path = strip_query_string(raw_path);
+        let Some(path_item) = resolve_path_item(swagger, raw_path, path_item) else {
+            continue;
+        };
         if let Some(obj) = path_item.as_object() {
             for (method, operation) in obj.iter() {
                 if !is_valid_http_method(method) {
                     continue;
                 }
 
-                let tag_name = extract_tag(operation).unwrap_or_else(|| "default".to_string());
-                let tag_normalized = normalize_tag(&tag_name);
-
-                // Apply tag filter if provided
-                // if let Some(ref filters) = tag_filters {
-                // 现代写法
-                if let Some(filters) = &tag_filters {
-                    if !filters.contains(&tag_normalized) {
-                        continue;
-                    }
+                // Match the filter against every tag, emitting into the first matching one,
+                // or into all matching ones with `--multi-tag`
+                let raw_tags = extract_tags(operation);
+                let all_tags: Vec<String> = raw_tags
+                    .iter()
+                    .map(|tag| normalize_tag(tag))
+                    .collect();
+                // Operations carrying any excluded tag are skipped entirely
+                if all_tags.iter().any(|tag| options.excluded_tags.contains(tag)) {
+                    continue;
+                }
+                let mut tags: Vec<String> = all_tags
+                    .into_iter()
+                    .filter(|tag| options.tag_filters.as_ref().is_none_or(|filters| filters.contains(tag)))
+                    .collect();
+                if !options.multi_tag {
+                    tags.truncate(1);
                 }
 
-                let api_op = parse_operation(
-                    operation,
-                    path,
-                    method,
-                    get_service(&mut service_map, &tag_normalized),
-                    &schemas_root
-                )?;
+                for tag_normalized in &tags {
+                    // Services are grouped case-insensitively, named after the first spelling seen
+                    let service_name = match raw_tags.iter().find(|tag| normalize_tag(tag) == *tag_normalized) {
+                        Some(tag) if options.preserve_tag_case => to_pascal_identifier(tag),
+                        _ => tag_normalized.clone(),
+                    };
+                    let service = get_service(&mut service_map, tag_normalized, &service_name);
+                    let mut api_op = parse_operation(operation, path, method, service, &ctx)?;
+                    api_op.base_path = base_path.clone();
 
-                get_service(&mut service_map, &tag_normalized).operations.push(api_op);
+                    get_service(&mut service_map, tag_normalized, &service_name).operations.push(api_op);
+                }
             }
         }
     }
 
-    // Extract type definitions from schemas
+    // Extract type definitions from schemas, repeating until every type referenced
+    // by an included type (e.g. union members) is included as well
     if let Some(schemas) = schemas_root {
         if let Some(schema_obj) = schemas.as_object() {
-            for (name, schema) in schema_obj.iter() {
-                for service in service_map.values_mut() {
-                    if should_include_type(name, &service.operations) {
-                        if let Ok(type_def) = extract_type_definition(name, schema) {
-                            service.type_definitions.insert(name.clone(), type_def);
+            for service in service_map.values_mut() {
+                loop {
+                    let mut added = false;
+                    for (key, schema) in schema_obj.iter() {
+                        let name = schema_type_name(key);
+                        if
+                            service.type_definitions.contains_key(&name) ||
+                            !service_references_type(service, &name)
+                        {
+                            continue;
                         }
+                        if let Ok(type_def) = named_type_definition(key, schema, &ctx) {
+                            service.type_definitions.insert(name, type_def);
+                            added = true;
+                        }
+                    }
+                    if !added {
+                        break;
                     }
                 }
             }
         }
     }
 
+    // Attach synthesized aliases to every service that references them
+    for alias in ctx.aliases.borrow().values() {
+        for service in service_map.values_mut() {
+            if service_references_type(service, &alias.name) {
+                service.type_definitions.insert(alias.name.clone(), alias.clone());
+            }
+        }
+    }
+
+    if options.omit_readonly_in_request {
+        for service in service_map.values_mut() {
+            omit_read_only_fields(service);
+        }
+    }
+    if options.omit_writeonly_in_response {
+        for service in service_map.values_mut() {
+            omit_write_only_fields(service);
+        }
+    }
+
     Ok(service_map.into_values().collect())
 }
 
-fn get_service<'a>(service_map: &'a mut BTreeMap<String, Service>, name: &str) -> &'a mut Service {
-    service_map.entry(name.to_string()).or_insert_with(|| Service {
+/// Merge the services parsed from several specs by name, ordering their operations by path
+/// and method (suffixing colliding function names unless `keep_duplicate_names`).
+/// On type name collisions with a different definition the first one wins with a warning.
+pub fn merge_services(
+    documents: Vec<Vec<Service>>,
+    sources: &[String],
+    keep_duplicate_names: bool
+) -> Vec<Service> {
+    let mut merged: BTreeMap<String, Service> = BTreeMap::new();
+    for (services, source) in documents.into_iter().zip(sources) {
+        for service in services {
+            let Some(existing) = merged.get_mut(&service.name) else {
+                merged.insert(service.name.clone(), service);
+                continue;
+            };
+            for mut operation in service.operations {
+                if !keep_duplicate_names {
+                    operation.function_name = unique_function_name(operation.function_name, &existing.operations);
+                }
+                existing.operations.push(operation);
+            }
+            for (name, type_def) in service.type_definitions {
+                match existing.type_definitions.get(&name) {
+                    Some(kept) if *kept != type_def => {
+                        eprintln!(
+                            "  ⚠ Type '{}' of service '{}' in {} differs from an earlier spec, keeping the first",
+                            name,
+                            service.name,
+                            source
+                        );
+                    }
+                    Some(_) => {}
+                    None => {
+                        existing.type_definitions.insert(name, type_def);
+                    }
+                }
+            }
+        }
+    }
+    // Order operations by path, then method, so the output does not depend on how the
+    // paths are laid out in the spec or spread over several specs
+    for service in merged.values_mut() {
+        service.operations.sort_by(|a, b| (&a.path, &a.method).cmp(&(&b.path, &b.method)));
+    }
+    merged.into_values().collect()
+}
+
+/// Replace named request body types that have `readOnly` fields with
+/// `Omit<T, 'id' | ...>`, so server-managed fields are not required in requests
+fn omit_read_only_fields(service: &mut Service) {
+    for operation in service.operations.iter_mut() {
+        let Some(body_type) = &operation.body_type else {
+            continue;
+        };
+        if let Some(omitted) = omit_fields(body_type, &service.type_definitions, TypeDefinition::read_only_fields) {
+            operation.body_type = Some(omitted);
+        }
+    }
+}
+
+/// Replace named response types (or arrays of them) that have `writeOnly` fields with
+/// `Omit<T, 'password' | ...>`, so request-only fields are not expected in responses
+fn omit_write_only_fields(service: &mut Service) {
+    for operation in service.operations.iter_mut() {
+        let response_type = &operation.response_type;
+        let omitted = match response_type.strip_suffix("[]") {
+            Some(element) => {
+                omit_fields(element, &service.type_definitions, TypeDefinition::write_only_fields).map(|t| format!("{}[]", t))
+            }
+            None => omit_fields(response_type, &service.type_definitions, TypeDefinition::write_only_fields),
+        };
+        if let Some(omitted) = omitted {
+            operation.response_type = omitted;
+        }
+    }
+}
+
+/// `Omit<T, 'a' | 'b'>` of the fields `pick` selects from the type named `type_name`,
+/// or `None` when it is not a declared type or no field is selected
+fn omit_fields(
+    type_name: &str,
+    type_definitions: &BTreeMap<String, TypeDefinition>,
+    pick: impl Fn(&TypeDefinition) -> Vec<&str>
+) -> Option<String> {
+    let type_def = type_definitions.get(type_name)?;
+    let fields = pick(type_def);
+    if fields.is_empty() {
+        return None;
+    }
+    let keys: Vec<String> = fields.iter().map(|name| format!("'{}'", name)).collect();
+    Some(format!("Omit<{}, {}>", type_name, keys.join(" | ")))
+}
+
+/// Check if any operation or type definition of the service mentions the type name
+fn service_references_type(service: &Service, type_name: &str) -> bool {
+    should_include_type(type_name, &service.operations) ||
+        service.type_definitions
+            .values()
+            .any(|t| {
+                t.alias.as_ref().is_some_and(|a| a.contains(type_name)) ||
+                    t.additional_properties.as_ref().is_some_and(|a| a.contains(type_name)) ||
+                    t.fields.values().any(|f| f.field_type.contains(type_name))
+            })
+}
+
+/// Fail when two operations end up with the same function name, either within a
+/// service or, with `NameScope::Global`, across all services
+pub fn validate_unique_function_names(services: &[Service], scope: NameScope) -> Result<()> {
+    let mut groups: Vec<BTreeMap<&str, Vec<String>>> = Vec::new();
+    for service in services {
+        if scope == NameScope::Service || groups.is_empty() {
+            groups.push(BTreeMap::new());
+        }
+        let names = groups.last_mut().unwrap();
+        for op in &service.operations {
+            names
+                .entry(op.function_name.as_str())
+                .or_default()
+                .push(format!("{} {} ({})", op.method, op.path, service.name));
+        }
+    }
+
+    let conflicts: Vec<String> = groups
+        .iter()
+        .flat_map(|names| names.iter())
+        .filter(|(_, ops)| ops.len() > 1)
+        .map(|(name, ops)| format!("  {}: {}", name, ops.join(", ")))
+        .collect();
+
+    if !conflicts.is_empty() {
+        anyhow::bail!("Duplicate operation names found:\n{}", conflicts.join("\n"));
+    }
+    Ok(())
+}
+
+fn get_service<'a>(service_map: &'a mut BTreeMap<String, Service>, key: &str, name: &str) -> &'a mut Service {
+    service_map.entry(key.to_string()).or_insert_with(|| Service {
         name: name.to_string(),
         operations: Vec::new(),
         type_definitions: BTreeMap::new(),
     })
 }
+/// Base path of the API: the path of OpenAPI 3 `servers[0].url` (with the origin stripped and
+/// server variables replaced by their defaults) or Swagger 2.0 `basePath`. `None` for the root.
+fn extract_base_path(swagger: &Value) -> Option<String> {
+    let path = if let Some(server) = swagger.get("servers").and_then(|s| s.get(0)) {
+        let mut url = server.get("url").and_then(|v| v.as_str())?.to_string();
+        if let Some(variables) = server.get("variables").and_then(|v| v.as_object()) {
+            for (name, variable) in variables {
+                if let Some(default) = variable.get("default").and_then(|v| v.as_str()) {
+                    url = url.replace(&format!("{{{}}}", name), default);
+                }
+            }
+        }
+        match url.split_once("://") {
+            Some((_, rest)) => rest.find('/').map(|idx| rest[idx..].to_string()).unwrap_or_default(),
+            None => url,
+        }
+    } else {
+        swagger.get("basePath").and_then(|v| v.as_str())?.to_string()
+    };
+
+    let path = path.trim_end_matches('/');
+    if path.is_empty() {
+        None
+    } else if path.starts_with('/') {
+        Some(path.to_string())
+    } else {
+        Some(format!("/{}", path))
+    }
+}
+
 /// Find schemas in either Swagger 2.0 or OpenAPI 3.0 format
-fn find_schemas(swagger: &Value) -> Option<Value> {
+fn find_schemas(swagger: &Value) -> Option<&Value> {
     if let Some(defs) = swagger.get("definitions") {
-        Some(defs.clone())
+        Some(defs)
     } else if let Some(components) = swagger.get("components") {
-        components.get("schemas").cloned()
+        components.get("schemas")
     } else {
         None
     }
 }
 
+/// Resolve a path item that is a `$ref` to a shared path item elsewhere in the document,
+/// e.g. `#/components/pathItems/Users`. Operations declared next to the `$ref` take precedence.
+/// Unresolvable references are reported and the path is skipped.
+fn resolve_path_item<'a>(swagger: &'a Value, path: &str, path_item: &'a Value) -> Option<Cow<'a, Value>> {
+    let mut resolved = Cow::Borrowed(path_item);
+    // Follow chains of references, bounded to stop on cycles
+    for _ in 0..8 {
+        let Some(ref_str) = resolved.get("$ref").and_then(|v| v.as_str()) else {
+            return Some(resolved);
+        };
+        let Some(target) = ref_str.strip_prefix('#').and_then(|pointer| swagger.pointer(pointer)) else {
+            eprintln!("  ⚠ Skipping path '{}': cannot resolve path item $ref '{}'", path, ref_str);
+            return None;
+        };
+        let mut merged = target.clone();
+        if let (Some(merged_obj), Some(local)) = (merged.as_object_mut(), resolved.as_object()) {
+            for (key, value) in local.iter().filter(|(key, _)| key.as_str() != "$ref") {
+                merged_obj.insert(key.clone(), value.clone());
+            }
+        }
+        resolved = Cow::Owned(merged);
+    }
+    eprintln!("  ⚠ Skipping path '{}': path item $refs are nested too deeply or circular", path);
+    None
+}
+
+/// Drop any query string accidentally included in a path key (e.g. `/search?type=x`).
+/// Query parameters belong in `parameters`, so the suffix is reported and ignored.
+fn strip_query_string(path: &str) -> &str {
+    let (path_only, query) = split_query_string(path);
+    if let Some(query) = query {
+        eprintln!("  ⚠ Ignoring query string '?{}' in path '{}', declare it in parameters instead", query, path);
+    }
+    path_only
+}
+
+/// Split a path key into the path and its query string, without the `?`
+fn split_query_string(path: &str) -> (&str, Option<&str>) {
+    match path.split_once('?') {
+        Some((path_only, query)) => (path_only, Some(query)),
+        None => (path, None),
+    }
+}
+
 /// Check if method is a valid HTTP method
 fn is_valid_http_method(method: &str) -> bool {
     matches!(method, "get" | "post" | "put" | "delete" | "patch" | "head" | "options")
 }
 
-/// Extract tag name from operation
-fn extract_tag(operation: &Value) -> Option<String> {
-    operation
+/// Extract the distinct tag names of an operation, or `default` when it has none
+fn extract_tags(operation: &Value) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in operation
         .get("tags")
         .and_then(|t| t.as_array())
-        .and_then(|arr| arr.first())
-        .and_then(|v| v.as_str())
-        .map(String::from)
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str()) {
+        if !tags.iter().any(|t| normalize_tag(t) == normalize_tag(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    if tags.is_empty() {
+        tags.push("default".to_string());
+    }
+    tags
 }
 
 /// Normalize tag name to lowercase
@@ -130,7 +731,7 @@ fn normalize_tag(tag: &str) -> String {
 fn should_include_type(type_name: &str, operations: &[ApiOperation]) -> bool {
     operations
         .iter()
-        .any(|op| { op.request_type.contains(type_name) || op.response_type.contains(type_name) })
+        .any(|op| op.referenced_types().iter().any(|t| t.contains(type_name)))
 }
 
 /// Parse a single API operation
@@ -139,36 +740,202 @@ fn parse_operation(
     path: &str,
     method: &str,
     service: &mut Service,
-    _schemas: &Option<Value>
+    ctx: &SchemaContext
 ) -> Result<ApiOperation> {
-    let function_name = extract_function_name(operation, method, path);
-    let (request_type, response_type) = extract_types(operation, service);
+    let function_name = extract_function_name(operation, method, path, ctx.options);
+    // Summaries repeat, and paths differing only in a trailing slash or in punctuation
+    // derive the same name, so keep the names distinct
+    let function_name = if ctx.options.keep_duplicate_names {
+        function_name
+    } else {
+        unique_function_name(function_name, &service.operations)
+    };
+    let types = extract_types(operation, path, &function_name, service, ctx);
     let operation_id = operation
         .get("operationId")
         .and_then(|v| v.as_str())
         .map(String::from);
+    let long_running =
+        operation.get("x-long-running").is_some_and(|v| v.as_bool() != Some(false)) ||
+        operation.get("responses").and_then(|r| r.get("202")).is_some();
 
     Ok(ApiOperation {
         path: path.to_string(),
         method: method.to_uppercase(),
         function_name,
-        request_type,
-        response_type,
+        base_path: None,
+        summary: text_field(operation, "summary"),
+        description: text_field(operation, "description"),
+        body_type: types.body_type,
+        body_optional: types.body_optional,
+        body_encoding: types.body_encoding,
+        error_type: types.error_type,
+        query_type: types.query_type,
+        query_optional: types.query_optional,
+        response_type: types.response_type,
         operation_id,
+        path_params: types.path_params,
+        header_params: types.header_params,
+        long_running,
+        deprecated: operation.get("deprecated").and_then(|v| v.as_bool()).unwrap_or(false),
+        secured: is_secured(operation, ctx.document),
+        param_docs: types.param_docs,
+        response_description: types.response_description,
+        response_example: extract_response_example(operation, ctx),
     })
 }
 
+/// Whether an operation requires authentication. Its own `security` overrides the document's,
+/// and `security: []` or an empty `{}` requirement makes authentication optional.
+fn is_secured(operation: &Value, document: &Value) -> bool {
+    operation
+        .get("security")
+        .or_else(|| document.get("security"))
+        .and_then(|v| v.as_array())
+        .is_some_and(|requirements| {
+            !requirements.is_empty() &&
+                requirements.iter().all(|r| r.as_object().is_some_and(|r| !r.is_empty()))
+        })
+}
+
+/// Read a free-text field such as `summary` or `description` of an operation, schema or parameter
+fn text_field(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(String::from)
+}
+
+/// Collect the path parameters of an operation: the `in: path` parameters ordered by their
+/// position in the path template, followed by template segments that are not declared
+fn extract_path_params(params: &[Value], path: &str, ctx: &SchemaContext) -> Vec<PathParam> {
+    let template: Vec<&str> = path
+        .split('{')
+        .skip(1)
+        .filter_map(|segment| segment.split_once('}').map(|(name, _)| name))
+        .collect();
+
+    let mut path_params: Vec<PathParam> = params
+        .iter()
+        .filter(|p| param_location(p) == "path")
+        .filter_map(|p| {
+            let name = p.get("name").and_then(|v| v.as_str())?;
+            template.contains(&name).then(|| PathParam {
+                name: name.to_string(),
+                arg_name: param_identifier(name),
+                field_type: param_type(p, ctx),
+            })
+        })
+        .collect();
+    path_params.sort_by_key(|p| template.iter().position(|t| *t == p.name));
+
+    for name in template {
+        if !path_params.iter().any(|p| p.name == name) {
+            path_params.push(PathParam {
+                name: name.to_string(),
+                arg_name: param_identifier(name),
+                field_type: "string".to_string(),
+            });
+        }
+    }
+    path_params
+}
+
+/// Collect the `in: header` params in declaration order
+fn extract_header_params(params: &[Value], ctx: &SchemaContext) -> Vec<HeaderParam> {
+    params
+        .iter()
+        .filter(|p| param_location(p) == "header")
+        .filter_map(|p| {
+            let name = p.get("name").and_then(|v| v.as_str())?;
+            Some(HeaderParam {
+                name: name.to_string(),
+                arg_name: param_identifier(name),
+                field_type: param_type(p, ctx),
+                optional: !p.get("required").and_then(|v| v.as_bool()).unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+/// Identifier used for a parameter argument, e.g. `user-id` -> `userId`
+fn param_identifier(name: &str) -> String {
+    let identifier = to_camel_identifier(name);
+    if identifier.is_empty() { "param".to_string() } else { identifier }
+}
+
+/// Location of a parameter (`path`, `query`, `header`, `body`, `formData`, `cookie`)
+fn param_location(param: &Value) -> &str {
+    param.get("in").and_then(|v| v.as_str()).unwrap_or("query")
+}
+
+/// TypeScript type of a parameter, from its 3.0 `schema` or 2.0 inline `type`
+fn param_type(param: &Value, ctx: &SchemaContext) -> String {
+    if let Some(schema) = param.get("schema") {
+        extract_type_name_from_schema(schema, ctx)
+    } else if let Some(type_str) = param.get("type").and_then(|v| v.as_str()) {
+        primitive_type_name(type_str, param, ctx)
+    } else {
+        "any".to_string()
+    }
+}
+
 /// Extract or generate function name
-fn extract_function_name(operation: &Value, method: &str, path: &str) -> String {
-    // First try operationId
-    if let Some(opid) = operation.get("operationId").and_then(|v| v.as_str()) {
-        return opid.to_string();
+fn extract_function_name(
+    operation: &Value,
+    method: &str,
+    path: &str,
+    options: &ParseOptions
+) -> String {
+    let operation_id = operation
+        .get("operationId")
+        .and_then(|v| v.as_str())
+        .map(sanitize_operation_id)
+        .filter(|name| !name.is_empty());
+
+    match options.operation_name_from {
+        OperationNameSource::OperationId => {
+            if let Some(opid) = operation_id {
+                return opid;
+            }
+        }
+        OperationNameSource::Summary => {
+            let summary = operation
+                .get("summary")
+                .and_then(|v| v.as_str())
+                .map(to_camel_identifier)
+                .filter(|name| !name.is_empty());
+            if let Some(name) = summary.or(operation_id) {
+                return name;
+            }
+        }
+        OperationNameSource::Path => {}
     }
 
     // Fallback: generate from method and path
     generate_function_name(method, path)
 }
 
+/// Keep an `operationId` that is a valid identifier as is, and camel-case the others,
+/// e.g. "users.get-by-id" -> "usersGetById", "2fa" -> "_2fa"
+fn sanitize_operation_id(operation_id: &str) -> String {
+    if is_identifier(operation_id) { operation_id.to_string() } else { to_camel_identifier(operation_id) }
+}
+
+/// Append a numeric suffix when the name is already taken by another operation of the service
+fn unique_function_name(name: String, operations: &[ApiOperation]) -> String {
+    let taken = |candidate: &str| operations.iter().any(|op| op.function_name == candidate);
+    if !taken(&name) {
+        return name;
+    }
+    (2..)
+        .map(|n| format!("{}{}", name, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap()
+}
+
 static METHOD_OP_MAP: [(&str, &str); 7] = [
     ("get", "Get"),
     ("post", "Create"),
@@ -179,7 +946,8 @@ static METHOD_OP_MAP: [(&str, &str); 7] = [
     ("options", "Options"),
 ];
 
-/// Generate camelCase function name from HTTP method and path
+/// Generate a function name from HTTP method and path, e.g. `DELETE /jobs/{job-id}` ->
+/// `DeleteJobsByJobId`, with path segments sanitized like `operationId`s
 fn generate_function_name(method: &str, path: &str) -> String {
     let method_lower = method.to_lowercase();
     let path_clean = path.replace('/', " ").replace('{', " by ").replace('}', "");
@@ -196,180 +964,1289 @@ fn generate_function_name(method: &str, path: &str) -> String {
         .to_string();
 
     for part in parts {
-        let capitalized = capitalize_first(part);
+        let capitalized = capitalize_first(&sanitize_operation_id(part));
         result.push_str(&capitalized);
     }
 
-    result
+    sanitize_operation_id(&result)
 }
 
-/// Capitalize first character of string
-fn capitalize_first(s: &str) -> String {
-    let mut chars = s.chars();
-    match chars.next() {
-        None => String::new(),
-        Some(first) => {
-            let mut result = String::new();
-            result.push_str(&first.to_uppercase().collect::<String>());
-            result.push_str(chars.as_str());
-            result
-        }
-    }
+/// Request body media types in order of preference, with the encoding they are sent with
+const REQUEST_CONTENT_TYPES: &[(&str, BodyEncoding)] = &[
+    ("application/json", BodyEncoding::Json),
+    ("application/x-www-form-urlencoded", BodyEncoding::FormUrlEncoded),
+    ("multipart/form-data", BodyEncoding::Multipart),
+];
+
+/// Request and response types of an operation
+struct OperationTypes {
+    path_params: Vec<PathParam>,
+    header_params: Vec<HeaderParam>,
+    query_type: Option<String>,
+    query_optional: bool,
+    body_type: Option<String>,
+    body_optional: bool,
+    body_encoding: BodyEncoding,
+    response_type: String,
+    error_type: Option<String>,
+    param_docs: Vec<(String, String)>,
+    response_description: Option<String>,
 }
 
-/// Extract request and response types from operation
-fn extract_types(operation: &Value, service: &mut Service) -> (String, String) {
-    let mut request_type = String::from("any");
-    let mut response_type = String::from("any");
+/// Extract request and response types from operation. Parameters are split by location:
+/// path params become positional arguments, query params an object type, header params
+/// a `headers` object, and the body comes from a 2.0 `in: body`/`formData` param or the 3.0 `requestBody`.
+fn extract_types(
+    operation: &Value,
+    path: &str,
+    function_name: &str,
+    service: &mut Service,
+    ctx: &SchemaContext
+) -> OperationTypes {
+    let params: Vec<Value> = operation
+        .get("parameters")
+        .and_then(|v| v.as_array())
+        .map(|params| params.iter().filter_map(|p| ctx.resolve_ref(p, "parameter").cloned()).collect())
+        .unwrap_or_default();
 
-    // Extract request type from parameters（2.0） or requestBody（3.0）
-    if let Some(params) = operation.get("parameters").and_then(|v| v.as_array()) {
-        for param in params {
-            if let Some(schema) = param.get("schema") {
-                request_type = extract_type_name_from_schema(schema);
-                if !request_type.is_empty() && request_type != "any" {
-                    break;
-                }
-            }
-        }
-    }
+    let path_params = extract_path_params(&params, path, ctx);
+    let header_params = extract_header_params(&params, ctx);
 
-    if request_type == "any" {
-        if let Some(rb) = operation.get("requestBody") {
-            if let Some(content) = rb.get("content") {
-                if let Some(appjson) = content.get("application/json") {
-                    if let Some(schema) = appjson.get("schema") {
-                        request_type = extract_type_name_from_schema(schema);
-                    }
-                }
+    //解析query parameters, 构建新的对象
+    let query_params: Vec<&Value> = params
+        .iter()
+        .filter(|p| param_location(p) == "query")
+        .collect();
+    let query_type = build_params_type(
+        &format!("{}Params", capitalize_first(function_name)),
+        &query_params,
+        service,
+        ctx
+    );
+    let query_optional = all_optional(&query_params);
+
+    let body_param = params.iter().find(|p| param_location(p) == "body");
+    let mut body_type = body_param
+        .and_then(|p| p.get("schema"))
+        .map(|schema| extract_type_name_from_schema(schema, ctx));
+    // Only an explicit `required: false` makes the body optional, most specs leave it out
+    let explicitly_optional = |value: &Value| value.get("required").and_then(|v| v.as_bool()) == Some(false);
+    let mut body_optional = body_param.is_some_and(explicitly_optional);
+
+    let mut body_description = body_param.and_then(|p| text_field(p, "description"));
+
+    let mut body_encoding = BodyEncoding::Json;
+    let mut form_params: Vec<&Value> = Vec::new();
+    if body_type.is_none() {
+        if let Some(rb) = operation.get("requestBody").and_then(|rb| ctx.resolve_ref(rb, "request body")) {
+            let media = rb.get("content").and_then(|content| {
+                REQUEST_CONTENT_TYPES.iter().find_map(|(media_type, encoding)| {
+                    content.get(*media_type).map(|media| (media, *encoding))
+                })
+            });
+            if let Some((_, encoding)) = media {
+                body_encoding = encoding;
             }
+            let schema = media.and_then(|(media, _)| media.get("schema"));
+            body_type = Some(schema.map_or("any".to_string(), |s| extract_type_name_from_schema(s, ctx)));
+            body_optional = explicitly_optional(rb);
+            body_description = text_field(rb, "description");
         }
     }
 
-    //解析parameters的每一个param, 构建新的对象
-    if request_type == "any" {
-        let params = operation
-            .get("parameters")
+    if body_type.is_none() {
+        form_params = params
+            .iter()
+            .filter(|p| param_location(p) == "formData")
+            .collect();
+        // 2.0 form params are urlencoded unless the operation consumes multipart, which
+        // `type: file` uploads require
+        let consumes_multipart = operation
+            .get("consumes")
             .and_then(|v| v.as_array())
-            .map(|v| v.to_owned())
-            .unwrap_or(vec![]);
-        if !params.is_empty() {
-            let type_name = format!("{}Request", capitalize_first(&service.name));
-            let mut custom_type = TypeDefinition {
-                name: type_name.clone(),
-                fields: BTreeMap::new(),
-                description: None,
-            };
-            for param in params {
-                if let Some(field_name) = param.get("name").and_then(|v| v.as_str()) {
-                    if let Some(field_type) = param.get("type").and_then(|v| v.as_str()) {
-                        let js_type = match field_type {
-                            "string" => "string",
-                            "integer" | "number" | "float" | "double" => "number",
-                            "boolean" => "boolean",
-                            _ => "any",
-                        };
-                        custom_type.fields.insert(field_name.to_string(), FieldData {
-                            field_type: js_type.to_string(),
-                            optional: param
-                                .get("required")
-                                .and_then(|v| v.as_bool().map(|b| !b))
-                                .unwrap_or(true),
-                            description: None,
-                        });
-                    }
-                }
-            }
-            request_type = type_name.clone();
-            service.type_definitions.insert(type_name.clone(), custom_type);
+            .is_some_and(|types| types.iter().any(|t| t.as_str() == Some("multipart/form-data")));
+        if consumes_multipart || form_params.iter().any(|p| p.get("type").and_then(|v| v.as_str()) == Some("file")) {
+            body_encoding = BodyEncoding::Multipart;
+        } else if !form_params.is_empty() {
+            body_encoding = BodyEncoding::FormUrlEncoded;
         }
+        body_type = build_params_type(
+            &format!("{}FormData", capitalize_first(function_name)),
+            &form_params,
+            service,
+            ctx
+        );
+        body_optional = all_optional(&form_params);
     }
 
     // Extract response type
+    let mut response_type = String::from("any");
+    let mut error_type = None;
+    let mut response_description = None;
     if let Some(responses) = operation.get("responses").and_then(|v| v.as_object()) {
-        let response_schema = responses
-            .get("200")
-            .or_else(|| responses.get("201"))
-            .or_else(|| responses.get("default"))
-            .or_else(|| responses.values().next());
-
-        if let Some(resp) = response_schema {
-            if let Some(schema) = resp.get("schema") {
-                response_type = extract_type_name_from_schema(schema);
-            } else if let Some(content) = resp.get("content") {
-                if let Some(appjson) = content.get("application/json") {
-                    if let Some(schema) = appjson.get("schema") {
-                        response_type = extract_type_name_from_schema(schema);
-                    }
-                }
-            }
+        if ctx.options.error_types {
+            error_type = error_response_union(responses, ctx);
+        }
+        let union = if ctx.options.union_success_responses { success_response_union(responses, ctx) } else { None };
+        let selected = select_success_response(responses, ctx);
+        if let Some(union) = union {
+            response_type = union;
+        } else if let Some((status, resp)) = selected {
+            response_type = response_type_name(status, resp, ctx);
+        }
+        response_description = selected.and_then(|(_, resp)| text_field(resp, "description"));
+    }
+
+    let param_docs = param_docs(&params, &path_params, &form_params, body_description);
+
+    OperationTypes {
+        path_params,
+        header_params,
+        query_type,
+        query_optional,
+        body_type,
+        body_optional,
+        body_encoding,
+        response_type: if response_type.is_empty() { "any".to_string() } else { response_type },
+        error_type,
+        param_docs,
+        response_description,
+    }
+}
+
+/// `@param` descriptions of the arguments in argument order: the path params, the query
+/// params as `params.page`, the body as `data` (or its 2.0 form fields as `data.file`) and
+/// the headers as `headers.xTenant`. Parameters without a description are left out.
+fn param_docs(
+    params: &[Value],
+    path_params: &[PathParam],
+    form_params: &[&Value],
+    body_description: Option<String>
+) -> Vec<(String, String)> {
+    let described = |name: String, param: &Value| text_field(param, "description").map(|text| (name, text));
+    let param_name = |param: &Value| param.get("name").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let located = |location: &'static str| params.iter().filter(move |p| param_location(p) == location);
+
+    let mut docs: Vec<(String, String)> = path_params
+        .iter()
+        .filter_map(|path_param| {
+            let param = located("path").find(|p| param_name(p) == path_param.name)?;
+            described(path_param.arg_name.clone(), param)
+        })
+        .collect();
+    docs.extend(located("query").filter_map(|p| described(format!("params.{}", param_name(p)), p)));
+    docs.extend(body_description.map(|text| ("data".to_string(), text)));
+    docs.extend(form_params.iter().filter_map(|p| described(format!("data.{}", param_name(p)), p)));
+    docs.extend(
+        located("header").filter_map(|p| described(format!("headers.{}", param_identifier(&param_name(p))), p))
+    );
+    docs
+}
+
+/// Whether none of the parameters is `required`, so the object built from them can be omitted
+fn all_optional(params: &[&Value]) -> bool {
+    params.iter().all(|p| p.get("required").and_then(|v| v.as_bool()) != Some(true))
+}
+
+/// Build an object type from individual parameters, registering it on the service
+/// (or inlining it when small). Returns `None` when there are no parameters.
+fn build_params_type(
+    type_name: &str,
+    params: &[&Value],
+    service: &mut Service,
+    ctx: &SchemaContext
+) -> Option<String> {
+    let mut custom_type = TypeDefinition {
+        name: type_name.to_string(),
+        fields: BTreeMap::new(),
+        description: None,
+        alias: None,
+        enum_members: Vec::new(),
+        additional_properties: None,
+        is_object: true,
+    };
+    for param in params {
+        if let Some(field_name) = param.get("name").and_then(|v| v.as_str()) {
+            custom_type.fields.insert(field_name.to_string(), FieldData {
+                field_type: param_type(param, ctx),
+                optional: param
+                    .get("required")
+                    .and_then(|v| v.as_bool().map(|b| !b))
+                    .unwrap_or(true),
+                description: text_field(param, "description"),
+                read_only: false,
+                write_only: false,
+                constraints: None,
+                deprecated: false,
+                default: None,
+                wire_name: None,
+            });
+        }
+    }
+
+    if custom_type.fields.is_empty() {
+        None
+    } else if ctx.should_inline(custom_type.fields.len()) {
+        Some(custom_type.to_inline_typescript())
+    } else {
+        service.type_definitions.insert(type_name.to_string(), custom_type);
+        Some(type_name.to_string())
+    }
+}
+
+/// Pick the response used for the return type, with its status: 200, then 201, another 2xx,
+/// default, or the first one, following a `$ref` to `components/responses` (or 2.0
+/// top-level `responses`)
+fn select_success_response<'v>(
+    responses: &'v serde_json::Map<String, Value>,
+    ctx: &SchemaContext<'v>
+) -> Option<(&'v str, &'v Value)> {
+    let (status, response) = ["200", "201"]
+        .iter()
+        .find_map(|status| responses.get_key_value(*status))
+        .or_else(|| responses.iter().find(|(status, _)| status.starts_with('2')))
+        .or_else(|| responses.get_key_value("default"))
+        .or_else(|| responses.iter().next())?;
+    Some((status.as_str(), ctx.resolve_ref(response, "response")?))
+}
+
+/// Type of the body of a response: `void` when it has none, `any` without a JSON schema
+fn response_type_name(status: &str, response: &Value, ctx: &SchemaContext) -> String {
+    if is_empty_response(status, response) {
+        return "void".to_string();
+    }
+    let schema = response
+        .get("schema")
+        .or_else(|| response.get("content")?.get("application/json")?.get("schema"));
+    match schema {
+        Some(schema) => extract_type_name_from_schema(schema, ctx),
+        None => "any".to_string(),
+    }
+}
+
+/// Union of the types of every 2xx response, e.g. `User | AcceptedJob`, `None` when
+/// there is no 2xx response. Collapses to `any` when one of them is untyped.
+fn success_response_union(responses: &serde_json::Map<String, Value>, ctx: &SchemaContext) -> Option<String> {
+    let mut types: Vec<String> = Vec::new();
+    for (status, response) in responses.iter().filter(|(status, _)| status.starts_with('2')) {
+        let Some(response) = ctx.resolve_ref(response, "response") else {
+            continue;
+        };
+        let type_name = response_type_name(status, response, ctx);
+        if type_name == "any" {
+            return Some(type_name);
+        }
+        if !types.contains(&type_name) {
+            types.push(type_name);
+        }
+    }
+    if types.is_empty() { None } else { Some(types.join(" | ")) }
+}
+
+/// Union of the typed 4xx/5xx responses, and of `default` when there is a 2xx response to
+/// succeed with, e.g. `ApiError | ValidationError`. `None` when none of them has a schema.
+fn error_response_union(responses: &serde_json::Map<String, Value>, ctx: &SchemaContext) -> Option<String> {
+    let has_success = responses.keys().any(|status| status.starts_with('2'));
+    let mut types: Vec<String> = Vec::new();
+    for (status, response) in responses {
+        let is_error = status.starts_with('4') || status.starts_with('5') || (status == "default" && has_success);
+        if !is_error {
+            continue;
+        }
+        let Some(response) = ctx.resolve_ref(response, "response") else {
+            continue;
+        };
+        let type_name = response_type_name(status, response, ctx);
+        if type_name != "any" && type_name != "void" && !types.contains(&type_name) {
+            types.push(type_name);
         }
     }
+    if types.is_empty() { None } else { Some(types.join(" | ")) }
+}
 
-    (
-        if request_type.is_empty() { "any".to_string() } else { request_type },
-        if response_type.is_empty() { "any".to_string() } else { response_type },
-    )
+/// Whether a response declares no body: `204`/`205`, or an explicitly empty `content`
+fn is_empty_response(status: &str, response: &Value) -> bool {
+    matches!(status, "204" | "205") ||
+        response.get("content").and_then(|c| c.as_object()).is_some_and(|content| content.is_empty())
 }
 
-/// Extract type name from schema (handles $ref)
-fn extract_type_name_from_schema(schema: &Value) -> String {
+/// Extract the example of the success response. Named `examples` (3.0/3.1 media types,
+/// 2.0 responses) win over 3.1 schema `examples` arrays, which win over singular `example`.
+fn extract_response_example(operation: &Value, ctx: &SchemaContext) -> Option<Example> {
+    let responses = operation.get("responses").and_then(|v| v.as_object())?;
+    let (_, response) = select_success_response(responses, ctx)?;
+
+    // Swagger 2.0: examples keyed by mime type, holding the example value directly
+    if let Some(value) = response.get("examples").and_then(|e| e.get("application/json")) {
+        return Some(Example { name: None, value: value.clone() });
+    }
+
+    let (media, schema) = match response.get("content") {
+        Some(content) => {
+            let media = content.get("application/json");
+            (media, media.and_then(|m| m.get("schema")))
+        }
+        None => (None, response.get("schema")),
+    };
+
+    media
+        .and_then(example_from_media)
+        .or_else(|| schema.and_then(|s| example_from_schema(s, ctx)))
+}
+
+/// Example of a media type object: the first entry of its `examples` map, or its `example`
+fn example_from_media(media: &Value) -> Option<Example> {
+    if let Some((name, example)) = media.get("examples").and_then(|e| e.as_object()?.iter().next()) {
+        if let Some(value) = example.get("value") {
+            return Some(Example { name: Some(name.clone()), value: value.clone() });
+        }
+    }
+    media.get("example").map(|value| Example { name: None, value: value.clone() })
+}
+
+/// Example of a schema: the first entry of a 3.1 `examples` array, or its `example`,
+/// following `$ref`s to the named schema (giving up on circular chains)
+fn example_from_schema(schema: &Value, ctx: &SchemaContext) -> Option<Example> {
+    let mut schema = schema;
+    let mut visited: Vec<String> = Vec::new();
+    while let Some(ref_str) = schema.get("$ref").and_then(|v| v.as_str()) {
+        let name = ref_schema_name(ref_str);
+        if visited.contains(&name) {
+            return None;
+        }
+        schema = ctx.schema(&name)?;
+        visited.push(name);
+    }
+    schema
+        .get("examples")
+        .and_then(|e| e.as_array())
+        .and_then(|e| e.first())
+        .or_else(|| schema.get("example"))
+        .map(|value| Example { name: None, value: value.clone() })
+}
+
+/// Name of the schema a `$ref` points at: the last segment of its JSON pointer, unescaped
+/// (`~1` -> `/`, `~0` -> `~`), e.g. `Foo/Bar` for `#/components/schemas/Foo~1Bar`
+fn ref_schema_name(ref_str: &str) -> String {
+    let segment = ref_str.rsplit('/').next().unwrap_or(ref_str);
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+/// TS type name of the schema stored under `key`, PascalCased when the key is not a valid
+/// identifier, e.g. `FooBar` for `Foo/Bar`
+fn schema_type_name(key: &str) -> String {
+    to_identifier(key)
+}
+
+/// Extract type name from schema (handles $ref). Nullable schemas (`nullable: true` in 3.0,
+/// a `null` entry in a 3.1 `type` array) get `| null` appended.
+fn extract_type_name_from_schema(schema: &Value, ctx: &SchemaContext) -> String {
+    let type_name = extract_non_null_type_name(schema, ctx);
+    if is_nullable(schema) && type_name != "any" && !type_name.split(" | ").any(|t| t == "null") {
+        format!("{} | null", type_name)
+    } else {
+        type_name
+    }
+}
+
+/// Check if the schema allows `null` through `nullable: true` or a 3.1 `type` array
+fn is_nullable(schema: &Value) -> bool {
+    schema.get("nullable").and_then(|v| v.as_bool()).unwrap_or(false) ||
+        schema
+            .get("type")
+            .and_then(|t| t.as_array())
+            .is_some_and(|types| types.iter().any(|t| t.as_str() == Some("null")))
+}
+
+fn extract_non_null_type_name(schema: &Value, ctx: &SchemaContext) -> String {
     if let Some(ref_str) = schema.get("$ref").and_then(|v| v.as_str()) {
-        return ref_str.split('/').last().unwrap_or("any").to_string();
+        return ref_type_name(&ref_schema_name(ref_str), ctx);
+    }
+
+    if let Some(union) = union_type_name(schema, ctx) {
+        return union;
+    }
+
+    if let Some(literals) = enum_literal_union(schema) {
+        return literals;
+    }
+
+    // A single-member allOf is the usual way to attach `nullable` or a description to a $ref
+    if let Some([member]) = schema.get("allOf").and_then(|v| v.as_array()).map(|v| v.as_slice()) {
+        return extract_type_name_from_schema(member, ctx);
+    }
+
+    if let Some(object) = inline_object_type(schema, ctx) {
+        return object;
+    }
+
+    // OpenAPI 3.1 type arrays, e.g. `["string", "integer", "null"]`
+    if let Some(types) = schema.get("type").and_then(|v| v.as_array()) {
+        let mut names: Vec<String> = Vec::new();
+        for type_str in types.iter().filter_map(|t| t.as_str()).filter(|t| *t != "null") {
+            let name = primitive_type_name(type_str, schema, ctx);
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        return if names.is_empty() { "any".to_string() } else { names.join(" | ") };
     }
 
     if let Some(type_str) = schema.get("type").and_then(|v| v.as_str()) {
-        match type_str {
-            "string" => "string".to_string(),
-            "integer" | "number" | "float" | "double" => "number".to_string(),
-            "boolean" => "boolean".to_string(),
-            "array" => {
-                if let Some(items) = schema.get("items") {
-                    format!("{}[]", extract_type_name_from_schema(items))
-                } else {
-                    "any[]".to_string()
+        primitive_type_name(type_str, schema, ctx)
+    } else {
+        "any".to_string()
+    }
+}
+
+/// Map a single JSON schema `type` to its TypeScript counterpart
+fn primitive_type_name(type_str: &str, schema: &Value, ctx: &SchemaContext) -> String {
+    match type_str {
+        "string" => {
+            let format = schema.get("format").and_then(|v| v.as_str());
+            if ctx.options.date_type == DateType::Date && matches!(format, Some("date-time" | "date")) {
+                "Date".to_string()
+            } else if format == Some("binary") {
+                "Blob".to_string()
+            } else {
+                "string".to_string()
+            }
+        }
+        "integer" | "number" | "float" | "double" => {
+            if type_str == "integer" && schema.get("format").and_then(|v| v.as_str()) == Some("int64") {
+                match ctx.options.int64 {
+                    Int64Type::Number => {}
+                    Int64Type::String => return "string".to_string(),
+                    Int64Type::Bigint => return "bigint".to_string(),
+                }
+            }
+            if ctx.options.branded_ranges {
+                if let Some(alias) = branded_range_alias(schema, ctx) {
+                    return alias;
                 }
             }
-            _ => "any".to_string(),
+            "number".to_string()
+        }
+        "boolean" => "boolean".to_string(),
+        "null" => "null".to_string(),
+        // 2.0 upload parameters
+        "file" => "Blob".to_string(),
+        "array" => {
+            if let Some(items) = schema.get("items") {
+                array_of(&extract_type_name_from_schema(items, ctx))
+            } else {
+                "any[]".to_string()
+            }
+        }
+        _ => "any".to_string(),
+    }
+}
+
+/// Build an array type, parenthesizing union and intersection element types:
+/// `(string | null)[]`, but `('a' | 'b')[][]` and `{ a: string | null }[]`
+fn array_of(element: &str) -> String {
+    if is_compound_type(element) { format!("({})[]", element) } else { format!("{}[]", element) }
+}
+
+/// Whether a type expression is a union or intersection at its top level, outside of
+/// brackets and string literals
+fn is_compound_type(expr: &str) -> bool {
+    split_top_level(expr, '|').len() > 1 || split_top_level(expr, '&').len() > 1
+}
+
+/// Deepest level of nested inline objects expanded before falling back to `any`
+const MAX_INLINE_DEPTH: usize = 5;
+
+/// Expand an anonymous object schema (inline `properties`, a multi-member `allOf` or an open
+/// `additionalProperties` map) into an inline type such as `{ city: string; zip?: string }`,
+/// recursively
+fn inline_object_type(schema: &Value, ctx: &SchemaContext) -> Option<String> {
+    let is_open_map = matches!(schema.get("additionalProperties"), Some(Value::Bool(true) | Value::Object(_)));
+    if schema.get("properties").is_none() && schema.get("allOf").is_none() && !is_open_map {
+        return None;
+    }
+    if ctx.inline_depth.get() >= MAX_INLINE_DEPTH {
+        return Some("any".to_string());
+    }
+
+    ctx.inline_depth.set(ctx.inline_depth.get() + 1);
+    let type_def = extract_type_definition("", schema, ctx);
+    ctx.inline_depth.set(ctx.inline_depth.get() - 1);
+
+    type_def.ok().map(|t| t.to_inline_typescript())
+}
+
+/// Resolve a `oneOf`/`anyOf` schema into a union of its member types, e.g. `Cat | Dog`.
+/// The union collapses to `any` when a member cannot be resolved. With a `discriminator`,
+/// each referenced member carries its discriminant literal so the union narrows on it:
+/// `Cat & { petType: 'cat' } | Dog & { petType: 'dog' }`.
+fn union_type_name(schema: &Value, ctx: &SchemaContext) -> Option<String> {
+    let members = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(|v| v.as_array())?;
+    let discriminator = schema.get("discriminator");
+    let property_name = discriminator
+        .and_then(|d| d.get("propertyName"))
+        .and_then(|v| v.as_str());
+
+    let mut types: Vec<String> = Vec::new();
+    for member in members {
+        let mut type_name = extract_type_name_from_schema(member, ctx);
+        if type_name == "any" {
+            return Some("any".to_string());
+        }
+        let member_ref = member.get("$ref").and_then(|v| v.as_str());
+        if let (Some(property_name), Some(member_ref)) = (property_name, member_ref) {
+            let value = discriminator_value(discriminator, member_ref);
+            type_name = format!("{} & {{ {}: {} }}", type_name, property_key(&field_key(property_name, ctx)), value);
         }
+        if !types.contains(&type_name) {
+            types.push(type_name);
+        }
+    }
+
+    if types.is_empty() { None } else { Some(types.join(" | ")) }
+}
+
+/// Discriminant literal of a `oneOf` member: the `mapping` key pointing at its `$ref`
+/// (by full reference or bare schema name), or the schema name itself
+fn discriminator_value(discriminator: Option<&Value>, member_ref: &str) -> String {
+    let schema_name = ref_schema_name(member_ref);
+    let mapped = discriminator
+        .and_then(|d| d.get("mapping"))
+        .and_then(|v| v.as_object())
+        .and_then(|mapping| {
+            mapping
+                .iter()
+                .find(|(_, target)| {
+                    target.as_str().is_some_and(|target| target == member_ref || target == schema_name)
+                })
+                .map(|(value, _)| value.as_str())
+        });
+    let value = mapped.unwrap_or(&schema_name);
+    ts_literal(&Value::String(value.to_string())).unwrap_or_default()
+}
+
+/// Collect the `minimum`/`maximum`, `minLength`/`maxLength` and `pattern` constraints of a
+/// schema, or `None` when it has none
+fn extract_constraints(schema: &Value) -> Option<Constraints> {
+    let number = |key: &str| schema.get(key).filter(|v| v.is_number()).map(|v| v.to_string());
+    let constraints = Constraints {
+        minimum: number("minimum"),
+        maximum: number("maximum"),
+        min_length: number("minLength"),
+        max_length: number("maxLength"),
+        pattern: schema.get("pattern").and_then(|v| v.as_str()).map(String::from),
+    };
+    if constraints == Constraints::default() { None } else { Some(constraints) }
+}
+
+/// Turn an `enum` array into a union of literals, e.g. `'ACTIVE' | 'INACTIVE'` or `1 | 2 | 3`
+fn enum_literal_union(schema: &Value) -> Option<String> {
+    let values = schema.get("enum").and_then(|v| v.as_array())?;
+    let literals: Vec<String> = values.iter().filter_map(ts_literal).collect();
+    if literals.is_empty() { None } else { Some(literals.join(" | ")) }
+}
+
+/// Collect the members of a named enum schema, naming them from `x-enum-varnames` when present
+fn extract_enum_members(schema: &Value) -> Vec<EnumMember> {
+    let Some(values) = schema.get("enum").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    let varnames: Vec<&str> = schema
+        .get("x-enum-varnames")
+        .and_then(|v| v.as_array())
+        .map(|names| names.iter().filter_map(|n| n.as_str()).collect())
+        .unwrap_or_default();
+
+    let mut members: Vec<EnumMember> = Vec::new();
+    for (idx, value) in values.iter().enumerate() {
+        let Some(literal) = ts_literal(value) else {
+            continue;
+        };
+        let base = match varnames.get(idx) {
+            Some(name) if values.len() == varnames.len() => to_identifier(name),
+            _ => enum_member_name(value),
+        };
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while members.iter().any(|m| m.name == name) {
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+        members.push(EnumMember { name, literal });
+    }
+    members
+}
+
+/// Derive an enum member identifier from its value: valid identifiers are kept as-is,
+/// other strings are PascalCased and numbers become `Value1`, `ValueNeg1`, ...
+fn enum_member_name(value: &Value) -> String {
+    match value {
+        Value::String(s) => to_identifier(s),
+        other => format!("Value{}", other.to_string().replace('-', "Neg").replace('.', "_")),
+    }
+}
+
+/// Keep a valid identifier unchanged, otherwise PascalCase it
+fn to_identifier(text: &str) -> String {
+    if is_identifier(text) {
+        text.to_string()
     } else {
-        "any".to_string()
+        let name = to_pascal_identifier(text);
+        if name.is_empty() { String::from("Empty") } else { name }
     }
 }
 
-/// Extract type definition from schema
-fn extract_type_definition(name: &str, schema: &Value) -> Result<TypeDefinition> {
-    let mut fields = BTreeMap::new();
+/// Render a JSON scalar as a TypeScript literal
+fn ts_literal(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Null => Some("null".to_string()),
+        _ => None,
+    }
+}
+
+/// Build (and register) a branded alias such as `number & { __min: 0; __max: 120 }` for a
+/// numeric schema with range constraints. Returns the alias name, or `None` when unconstrained.
+fn branded_range_alias(schema: &Value, ctx: &SchemaContext) -> Option<String> {
+    let number = |key: &str| schema.get(key).filter(|v| v.is_number()).map(|v| v.to_string());
+    // OpenAPI 3.0 marks exclusivity with a boolean next to minimum/maximum, 3.1 uses numbers
+    let exclusive = |key: &str| schema.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let mut bounds: Vec<(&str, &str, String)> = Vec::new();
+    if let Some(min) = number("exclusiveMinimum") {
+        bounds.push(("Gt", "__exclusiveMin", min));
+    } else if let Some(min) = number("minimum") {
+        if exclusive("exclusiveMinimum") {
+            bounds.push(("Gt", "__exclusiveMin", min));
+        } else {
+            bounds.push(("Min", "__min", min));
+        }
+    }
+    if let Some(max) = number("exclusiveMaximum") {
+        bounds.push(("Lt", "__exclusiveMax", max));
+    } else if let Some(max) = number("maximum") {
+        if exclusive("exclusiveMaximum") {
+            bounds.push(("Lt", "__exclusiveMax", max));
+        } else {
+            bounds.push(("Max", "__max", max));
+        }
+    }
+    if bounds.is_empty() {
+        return None;
+    }
+
+    let name = bounds.iter().fold(String::from("Number"), |mut name, (prefix, _, value)| {
+        name.push_str(prefix);
+        name.push_str(&value.replace('-', "Neg").replace(['.', '+'], "_"));
+        name
+    });
+    let brand = bounds
+        .iter()
+        .map(|(_, key, value)| format!("{}: {}", key, value))
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    ctx.aliases
+        .borrow_mut()
+        .entry(name.clone())
+        .or_insert_with(|| TypeDefinition::alias(&name, format!("number & {{ {} }}", brand)));
+    Some(name)
+}
+
+/// Resolve the TS type of a `$ref`ed schema name: the element type array of a named array
+/// schema, the name itself, or its inline expansion with `--inline-small-types`.
+/// Memoized for top-level lookups.
+fn ref_type_name(name: &str, ctx: &SchemaContext) -> String {
+    let top_level = ctx.is_top_level();
+    if top_level {
+        if let Some(cached) = ctx.ref_types.borrow().get(name) {
+            return cached.clone();
+        }
+    }
+
+    let type_name = named_array_type(name, ctx)
+        .or_else(|| inline_small_ref(name, ctx))
+        .unwrap_or_else(|| schema_type_name(name));
+    if top_level {
+        ctx.ref_types.borrow_mut().insert(name.to_string(), type_name.clone());
+    }
+    type_name
+}
+
+/// Follow a `$ref` to a named array schema and resolve it to its element type, e.g. `Foo[]`,
+/// so the element type is what operations reference. `None` for non-array schemas.
+fn named_array_type(name: &str, ctx: &SchemaContext) -> Option<String> {
+    let schema = ctx.schema(name)?;
+    if schema.get("type").and_then(|v| v.as_str()) != Some("array") {
+        return None;
+    }
+    if ctx.expanding.borrow().iter().any(|n| n == name) {
+        return None;
+    }
+
+    ctx.expanding.borrow_mut().push(name.to_string());
+    let type_name = primitive_type_name("array", schema, ctx);
+    ctx.expanding.borrow_mut().pop();
+    Some(type_name)
+}
+
+/// Extract the type definition of a named schema, memoized for top-level lookups
+/// so schemas shared by many services are only processed once
+fn named_type_definition(
+    name: &str,
+    schema: &Value,
+    ctx: &SchemaContext
+) -> Result<TypeDefinition> {
+    let top_level = ctx.is_top_level();
+    if top_level {
+        if let Some(cached) = ctx.definitions.borrow().get(name) {
+            return Ok(cached.clone());
+        }
+    }
+
+    // References back to the type being defined, directly or through other schemas,
+    // resolve to its name instead of expanding it again
+    ctx.expanding.borrow_mut().push(name.to_string());
+    let type_def = extract_type_definition(name, schema, ctx);
+    ctx.expanding.borrow_mut().pop();
+    let type_def = type_def?;
+    if top_level {
+        ctx.definitions.borrow_mut().insert(name.to_string(), type_def.clone());
+    }
+    Ok(type_def)
+}
+
+/// Expand a referenced object schema inline when it has fewer fields than the
+/// `--inline-small-types` threshold. Returns `None` when the reference should stay named.
+fn inline_small_ref(name: &str, ctx: &SchemaContext) -> Option<String> {
+    ctx.options.inline_small_types?;
+    let schema = ctx.schema(name)?;
+    if ctx.expanding.borrow().iter().any(|n| n == name) {
+        return None;
+    }
+
+    ctx.expanding.borrow_mut().push(name.to_string());
+    let type_def = extract_type_definition(name, schema, ctx);
+    ctx.expanding.borrow_mut().pop();
+
+    type_def
+        .ok()
+        .filter(|t| ctx.should_inline(t.fields.len()))
+        .map(|t| t.to_inline_typescript())
+}
+
+/// Collect the `properties` and `required` names of an object schema, merging every
+/// `allOf` member (resolving `$ref`s against the schemas root). Later members win on collision.
+fn collect_object_members(
+    schema: &Value,
+    ctx: &SchemaContext,
+    properties: &mut BTreeMap<String, Value>,
+    required: &mut HashSet<String>
+) {
+    if let Some(members) = schema.get("allOf").and_then(|v| v.as_array()) {
+        for member in members {
+            match member.get("$ref").and_then(|v| v.as_str()) {
+                Some(ref_str) => {
+                    let name = ref_schema_name(ref_str);
+                    let Some(target) = ctx.schema(&name) else {
+                        continue;
+                    };
+                    if ctx.expanding.borrow().contains(&name) {
+                        continue;
+                    }
+                    ctx.expanding.borrow_mut().push(name);
+                    collect_object_members(target, ctx, properties, required);
+                    ctx.expanding.borrow_mut().pop();
+                }
+                None => collect_object_members(member, ctx, properties, required),
+            }
+        }
+    }
 
     if let Some(props) = schema.get("properties").and_then(|p| p.as_object()) {
-        let required_fields = schema
-            .get("required")
-            .and_then(|r| r.as_array())
-            .map(|v| v.to_owned())
-            .unwrap_or(vec![]);
-        let required_fields_set: HashSet<String> = required_fields
-            .iter()
-            .map(|v| v.as_str())
-            .map(|v| v.expect("required field is not a string").to_string())
-            .collect();
         for (field_name, field_schema) in props.iter() {
-            let field_type = extract_type_name_from_schema(field_schema);
-            fields.insert(field_name.clone(), FieldData {
-                field_type,
-                optional: !required_fields_set.contains(field_name.as_str()),
-                description: None,
-            });
+            properties.insert(field_name.clone(), field_schema.clone());
+        }
+    }
+
+    if let Some(names) = schema.get("required").and_then(|r| r.as_array()) {
+        required.extend(names.iter().filter_map(|v| v.as_str()).map(String::from));
+    }
+}
+
+/// Key of a property in the generated types, camelCased with `--camel-fields`
+fn field_key(name: &str, ctx: &SchemaContext) -> String {
+    if ctx.options.camel_fields { to_camel_case(name) } else { name.to_string() }
+}
+
+/// Extract type definition from schema
+fn extract_type_definition(
+    name: &str,
+    schema: &Value,
+    ctx: &SchemaContext
+) -> Result<TypeDefinition> {
+    let has_properties = schema.get("properties").is_some() || schema.get("allOf").is_some();
+    if !has_properties {
+        let union = union_type_name(schema, ctx).or_else(|| enum_literal_union(schema));
+        if let Some(union) = union {
+            let mut type_def = TypeDefinition::alias(&schema_type_name(name), union);
+            type_def.description = schema
+                .get("description")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            type_def.enum_members = extract_enum_members(schema);
+            return Ok(type_def);
+        }
+        if schema.get("type").and_then(|v| v.as_str()) == Some("array") {
+            // Keep self-referencing arrays (`Tree = Tree[]`) pointing at the name
+            ctx.expanding.borrow_mut().push(name.to_string());
+            let element_array = primitive_type_name("array", schema, ctx);
+            ctx.expanding.borrow_mut().pop();
+            let mut type_def = TypeDefinition::alias(&schema_type_name(name), element_array);
+            type_def.description = schema
+                .get("description")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            return Ok(type_def);
         }
     }
 
+    let mut properties = BTreeMap::new();
+    let mut required = HashSet::new();
+    collect_object_members(schema, ctx, &mut properties, &mut required);
+
+    let mut fields = BTreeMap::new();
+    for (field_name, field_schema) in properties.iter() {
+        let field_type = extract_type_name_from_schema(field_schema, ctx);
+        let key = field_key(field_name, ctx);
+        // Keep the wire name when the camelCase one is taken by another property
+        let key = if key != *field_name && properties.contains_key(&key) { field_name.clone() } else { key };
+        fields.insert(key.clone(), FieldData {
+            field_type,
+            optional: !required.contains(field_name.as_str()),
+            description: text_field(field_schema, "description"),
+            read_only: field_schema.get("readOnly").and_then(|v| v.as_bool()).unwrap_or(false),
+            write_only: field_schema.get("writeOnly").and_then(|v| v.as_bool()).unwrap_or(false),
+            constraints: extract_constraints(field_schema),
+            deprecated: field_schema.get("deprecated").and_then(|v| v.as_bool()).unwrap_or(false),
+            default: field_schema.get("default").map(|v| v.to_string()),
+            wire_name: Some(field_name.clone()).filter(|name| *name != key),
+        });
+    }
+
     let description = schema
         .get("description")
         .and_then(|v| v.as_str())
         .map(String::from);
 
     Ok(TypeDefinition {
-        name: name.to_string(),
+        name: schema_type_name(name),
         fields,
         description,
+        alias: None,
+        enum_members: Vec::new(),
+        additional_properties: additional_properties_type(schema, ctx),
+        is_object: has_properties || schema.get("type").and_then(|v| v.as_str()) == Some("object"),
     })
 }
+
+/// Value type of the open map described by `additionalProperties`: the resolved schema type,
+/// or `any` when it is just `true`. `None` when absent or `false`.
+fn additional_properties_type(schema: &Value, ctx: &SchemaContext) -> Option<String> {
+    match schema.get("additionalProperties")? {
+        Value::Bool(true) => Some("any".to_string()),
+        value @ Value::Object(_) => Some(extract_type_name_from_schema(value, ctx)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::cli::ArgsStyle;
+
+    /// Parse a 3.0 spec with the given paths and schemas using default options
+    fn parse(paths: Value, schemas: Value) -> Vec<Service> {
+        parse_with(paths, schemas, &ParseOptions::default())
+    }
+
+    fn parse_with(paths: Value, schemas: Value, options: &ParseOptions) -> Vec<Service> {
+        let spec = json!({
+            "openapi": "3.0.0",
+            "paths": paths,
+            "components": { "schemas": schemas },
+        });
+        parse_swagger(&spec, options).unwrap()
+    }
+
+    /// Paths of a single `GET /items` answering with `schema`
+    fn items_paths(schema: Value) -> Value {
+        json!({
+            "/items": {
+                "get": {
+                    "responses": { "200": { "content": { "application/json": { "schema": schema } } } }
+                }
+            }
+        })
+    }
+
+    /// Response type of `GET /items` answering with `schema`
+    fn response_type(schema: Value, schemas: Value) -> String {
+        parse(items_paths(schema), schemas)[0].operations[0].response_type.clone()
+    }
+
+    #[test]
+    fn query_strings_are_split_off_paths() {
+        assert_eq!(split_query_string("/search?type=x"), ("/search", Some("type=x")));
+        assert_eq!(split_query_string("/search"), ("/search", None));
+
+        let paths = json!({ "/search?type=x": { "get": { "responses": { "200": { "description": "OK" } } } } });
+        let services = parse(paths, json!({}));
+        let operation = &services[0].operations[0];
+        assert_eq!(operation.path, "/search");
+        assert_eq!(operation.function_name, "GetSearch");
+    }
+
+    #[test]
+    fn small_types_are_inlined_below_the_threshold() {
+        let object = |fields: &[&str]| {
+            let properties: serde_json::Map<String, Value> = fields
+                .iter()
+                .map(|field| (field.to_string(), json!({ "type": "string" })))
+                .collect();
+            json!({ "type": "object", "properties": properties })
+        };
+        let schemas = json!({
+            "Empty": object(&[]),
+            "Pair": object(&["a", "b"]),
+            "Triple": object(&["a", "b", "c"]),
+        });
+        let options = ParseOptions { inline_small_types: Some(3), ..Default::default() };
+        let response_type = |name: &str| {
+            let schema = json!({ "$ref": format!("#/components/schemas/{}", name) });
+            let services = parse_with(items_paths(schema), schemas.clone(), &options);
+            let operation = &services[0].operations[0];
+            (operation.response_type.clone(), services[0].type_definitions.contains_key(name))
+        };
+
+        // Fewer fields than the threshold are inlined, and the type is not emitted
+        assert_eq!(response_type("Pair"), ("{ a?: string; b?: string }".to_string(), false));
+        // As many fields as the threshold keep their name
+        assert_eq!(response_type("Triple"), ("Triple".to_string(), true));
+        // Field-less objects are never inlined
+        assert_eq!(response_type("Empty"), ("Empty".to_string(), true));
+    }
+
+    #[test]
+    fn array_responses_resolve_to_element_arrays() {
+        let schemas = json!({
+            "Pet": { "type": "object", "properties": { "name": { "type": "string" } } },
+            "Pets": { "type": "array", "items": { "$ref": "#/components/schemas/Pet" } },
+        });
+        let inline = json!({ "type": "array", "items": { "$ref": "#/components/schemas/Pet" } });
+        let named = json!({ "$ref": "#/components/schemas/Pets" });
+        for schema in [inline, named] {
+            let services = parse(items_paths(schema), schemas.clone());
+            assert_eq!(services[0].operations[0].response_type, "Pet[]");
+            // The element type is emitted along with the operation
+            assert!(services[0].type_definitions.contains_key("Pet"));
+        }
+    }
+
+    #[test]
+    fn path_query_and_body_on_one_operation() {
+        let paths = json!({
+            "/users/{userId}/notes": {
+                "post": {
+                    "operationId": "createNote",
+                    "parameters": [
+                        { "name": "userId", "in": "path", "required": true, "schema": { "type": "integer" } },
+                        { "name": "notify", "in": "query", "schema": { "type": "boolean" } },
+                    ],
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Note" } } }
+                    },
+                    "responses": { "200": { "description": "OK" } }
+                }
+            }
+        });
+        let schemas = json!({ "Note": { "type": "object", "properties": { "text": { "type": "string" } } } });
+        let services = parse(paths, schemas);
+        let operation = &services[0].operations[0];
+        assert_eq!(operation.path_params.len(), 1);
+        assert_eq!(operation.query_type.as_deref(), Some("CreateNoteParams"));
+        assert_eq!(operation.body_type.as_deref(), Some("Note"));
+        assert_eq!(
+            operation.parameter_list(ArgsStyle::Positional, false),
+            "userId: number, params: CreateNoteParams, data: Note"
+        );
+    }
+
+    #[test]
+    fn referenced_request_bodies_resolve_to_named_schemas() {
+        let spec = json!({
+            "openapi": "3.0.0",
+            "paths": {
+                "/pets": {
+                    "post": {
+                        "operationId": "createPet",
+                        "requestBody": { "$ref": "#/components/requestBodies/PetBody" },
+                        "responses": { "200": { "description": "OK" } }
+                    }
+                }
+            },
+            "components": {
+                "requestBodies": {
+                    "PetBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } }
+                    }
+                },
+                "schemas": { "Pet": { "type": "object", "properties": { "name": { "type": "string" } } } }
+            }
+        });
+        let services = parse_swagger(&spec, &ParseOptions::default()).unwrap();
+        let operation = &services[0].operations[0];
+        assert_eq!(operation.body_type.as_deref(), Some("Pet"));
+        assert!(!operation.body_optional);
+        assert!(services[0].type_definitions.contains_key("Pet"));
+    }
+
+    #[test]
+    fn circular_refs_resolve_to_their_names() {
+        let schemas = json!({
+            "Node": {
+                "type": "object",
+                "properties": {
+                    "children": { "type": "array", "items": { "$ref": "#/components/schemas/Node" } },
+                    "owner": { "$ref": "#/components/schemas/Owner" }
+                }
+            },
+            "Owner": { "type": "object", "properties": { "node": { "$ref": "#/components/schemas/Node" } } },
+        });
+        let schema = json!({ "$ref": "#/components/schemas/Node" });
+        let services = parse(items_paths(schema.clone()), schemas.clone());
+        let type_defs = &services[0].type_definitions;
+        assert_eq!(type_defs["Node"].fields["children"].field_type, "Node[]");
+        assert_eq!(type_defs["Node"].fields["owner"].field_type, "Owner");
+        assert_eq!(type_defs["Owner"].fields["node"].field_type, "Node");
+
+        // Inlining small types expands references, which has to stop at the cycles too
+        let options = ParseOptions { inline_small_types: Some(3), ..Default::default() };
+        let services = parse_with(items_paths(schema), schemas, &options);
+        let type_defs = &services[0].type_definitions;
+        assert_eq!(services[0].operations[0].response_type, "{ children?: Node[]; owner?: { node?: Node } }");
+        assert_eq!(type_defs["Node"].fields["owner"].field_type, "{ node?: Node }");
+    }
+
+    /// Function names of every operation, in service order
+    fn function_names(services: &[Service]) -> Vec<String> {
+        services
+            .iter()
+            .flat_map(|service| service.operations.iter().map(|op| op.function_name.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn colliding_path_names_are_suffixed() {
+        let ok = json!({ "200": { "description": "OK" } });
+        let paths = json!({
+            "/users/{id}": { "get": { "responses": ok } },
+            "/users/{id}/": { "get": { "responses": ok } },
+        });
+        assert_eq!(function_names(&parse(paths, json!({}))), ["GetUsersById", "GetUsersById2"]);
+    }
+
+    #[test]
+    fn path_names_are_sanitized() {
+        assert_eq!(generate_function_name("delete", "/jobs/{job-id}"), "DeleteJobsByJobId");
+        assert_eq!(generate_function_name("get", "/users.json"), "GetUsersJson");
+        assert_eq!(generate_function_name("trace", "/2fa"), "_2fa");
+    }
+
+    #[test]
+    fn operation_ids_are_sanitized() {
+        assert_eq!(sanitize_operation_id("users.get-by-id"), "usersGetById");
+        assert_eq!(sanitize_operation_id("get user"), "getUser");
+        assert_eq!(sanitize_operation_id("2fa"), "_2fa");
+        assert_eq!(sanitize_operation_id("listUsers"), "listUsers");
+    }
+
+    #[test]
+    fn operation_ids_are_sanitized_before_deduplication() {
+        let ok = json!({ "200": { "description": "OK" } });
+        let paths = json!({
+            "/a": { "get": { "operationId": "users.get-by-id", "responses": ok } },
+            "/b": { "get": { "operationId": "usersGetById", "responses": ok } },
+            "/c": { "get": { "operationId": "get user", "responses": ok } },
+        });
+        assert_eq!(function_names(&parse(paths, json!({}))), ["usersGetById", "usersGetById2", "getUser"]);
+    }
+
+    #[test]
+    fn operations_are_ordered_by_path_and_method() {
+        let ok = json!({ "200": { "description": "OK" } });
+        let first = parse(
+            json!({ "/users": { "post": { "tags": ["users"], "responses": ok }, "get": { "tags": ["users"], "responses": ok } } }),
+            json!({})
+        );
+        let second = parse(
+            json!({
+                "/users/{id}": { "delete": { "tags": ["users"], "responses": ok } },
+                "/accounts": { "get": { "tags": ["users"], "responses": ok } },
+            }),
+            json!({})
+        );
+        let sources = ["a.json".to_string(), "b.json".to_string()];
+
+        let expected = [
+            ("/accounts", "GET"),
+            ("/users", "GET"),
+            ("/users", "POST"),
+            ("/users/{id}", "DELETE"),
+        ];
+        // Whatever order the specs are given in
+        for documents in [vec![first.clone(), second.clone()], vec![second, first]] {
+            let services = merge_services(documents, &sources, false);
+            let order: Vec<(&str, &str)> = services[0].operations
+                .iter()
+                .map(|op| (op.path.as_str(), op.method.as_str()))
+                .collect();
+            assert_eq!(order, expected);
+        }
+    }
+
+    #[test]
+    fn escaped_pointer_segments_are_unescaped() {
+        assert_eq!(ref_schema_name("#/components/schemas/Foo~1Bar"), "Foo/Bar");
+        assert_eq!(ref_schema_name("#/components/schemas/Foo~0Bar"), "Foo~Bar");
+        // `~01` is an escaped `~` followed by `1`, not an escaped `/`
+        assert_eq!(ref_schema_name("#/components/schemas/A~01"), "A~1");
+
+        let schemas = json!({ "Foo/Bar": { "type": "object", "properties": { "id": { "type": "string" } } } });
+        let schema = json!({ "$ref": "#/components/schemas/Foo~1Bar" });
+        let services = parse(items_paths(schema), schemas);
+        // The reference finds the schema under its unescaped key, and both the reference and
+        // the declaration use a valid identifier
+        assert_eq!(services[0].operations[0].response_type, "FooBar");
+        let type_def = &services[0].type_definitions["FooBar"];
+        assert_eq!(type_def.name, "FooBar");
+        assert!(type_def.fields.contains_key("id"));
+    }
+
+    #[test]
+    fn nested_arrays_of_primitives() {
+        let string_2d = json!({ "type": "array", "items": { "type": "array", "items": { "type": "string" } } });
+        assert_eq!(response_type(string_2d, json!({})), "string[][]");
+
+        let number_3d = json!({
+            "type": "array",
+            "items": { "type": "array", "items": { "type": "array", "items": { "type": "number" } } }
+        });
+        assert_eq!(response_type(number_3d, json!({})), "number[][][]");
+
+        let nullable_2d = json!({
+            "type": "array",
+            "items": { "type": "array", "items": { "type": "string", "nullable": true } }
+        });
+        assert_eq!(response_type(nullable_2d, json!({})), "(string | null)[][]");
+    }
+
+    #[test]
+    fn nested_arrays_of_refs() {
+        let schemas = json!({
+            "Foo": { "type": "object", "properties": { "id": { "type": "string" } } },
+            "MaybeFoo": { "nullable": true, "allOf": [{ "$ref": "#/components/schemas/Foo" }] },
+        });
+        let foo_2d = json!({ "type": "array", "items": { "type": "array", "items": { "$ref": "#/components/schemas/Foo" } } });
+        assert_eq!(response_type(foo_2d, schemas.clone()), "Foo[][]");
+
+        let foo_3d = json!({
+            "type": "array",
+            "items": { "type": "array", "items": { "type": "array", "items": { "$ref": "#/components/schemas/Foo" } } }
+        });
+        assert_eq!(response_type(foo_3d, schemas.clone()), "Foo[][][]");
+
+        let nullable_foo_2d = json!({
+            "type": "array",
+            "items": { "type": "array", "items": { "$ref": "#/components/schemas/MaybeFoo" } }
+        });
+        assert_eq!(response_type(nullable_foo_2d, schemas.clone()), "MaybeFoo[][]");
+
+        let inline_nullable_2d = json!({
+            "type": "array",
+            "items": {
+                "type": "array",
+                "items": { "nullable": true, "allOf": [{ "$ref": "#/components/schemas/Foo" }] }
+            }
+        });
+        assert_eq!(response_type(inline_nullable_2d, schemas), "(Foo | null)[][]");
+    }
+
+    #[test]
+    fn compound_types() {
+        assert!(is_compound_type("string | null"));
+        assert!(is_compound_type("Foo & Bar"));
+        assert!(!is_compound_type("{ a: string | null }"));
+        assert!(!is_compound_type("('a' | 'b')[]"));
+        assert!(!is_compound_type("'a|b'"));
+        assert!(!is_compound_type("Record<string, number | null>"));
+    }
+
+    #[test]
+    fn yaml_specs_are_read_by_content() {
+        // No extension: the content alone tells YAML from JSON
+        let path = std::env::temp_dir().join(format!("ropenapi-apidocs-{}", std::process::id()));
+        let yaml = "\
+openapi: 3.0.0
+paths:
+  /pets/{id}:
+    get:
+      operationId: getPet
+      parameters:
+        - { name: id, in: path, required: true, schema: { type: integer } }
+      responses:
+        200:
+          description: OK
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Pet'
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name:
+          type: string
+";
+        fs::write(&path, yaml).unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let spec = read_swagger_file(&path, &[], false, false).unwrap();
+        let streamed = read_swagger_streaming(&path, &[], false, false).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        for spec in [spec, streamed] {
+            let services = parse_swagger(&spec, &ParseOptions::default()).unwrap();
+            let operation = &services[0].operations[0];
+            assert_eq!(operation.function_name, "getPet");
+            assert_eq!(operation.response_type, "Pet");
+            assert!(services[0].type_definitions["Pet"].fields.contains_key("name"));
+        }
+    }
+
+    #[test]
+    fn documents_neither_yaml_nor_json_report_both_errors() {
+        let err = parse_yaml_or_json("api", "openapi: [3.0.0\n").unwrap_err().to_string();
+        assert!(err.starts_with("api is neither YAML nor JSON"));
+        assert!(err.contains("\n  YAML: ") && err.contains("\n  JSON: "));
+    }
+}
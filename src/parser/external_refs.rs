@@ -0,0 +1,338 @@
+//! Resolution of `$ref`s pointing into other files, e.g. `./common.json#/Address`
+
+use anyhow::{ bail, Context, Result };
+use serde_json::{ Map, Value };
+use std::collections::{ HashMap, HashSet };
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use crate::naming::{ is_identifier, to_pascal_identifier };
+
+/// Copy the schemas referenced from other files into the schemas root of `spec`, read from
+/// `spec_path`, and point their `$ref`s at the copies. Files are resolved relative to the
+/// file holding the reference and read once. A copy is named after the last segment of its
+/// pointer (the file name without one), prefixed with the file name when the name is taken.
+/// A root schema that only references another file is replaced by its target. References
+/// outside schemas, e.g. to shared parameters, are replaced by their target. With `verbose`,
+/// every file read and every reuse of an already read one is reported, then their counts.
+pub fn inline_external_refs(spec: &mut Value, spec_path: &Path, verbose: bool) -> Result<()> {
+    let swagger2 = spec.get("swagger").is_some();
+    let mut resolver = Resolver::new(if swagger2 { "#/definitions/" } else { "#/components/schemas/" }, verbose);
+
+    let root_pointer = if swagger2 { "/definitions" } else { "/components/schemas" };
+    if let Some(schemas) = spec.pointer_mut(root_pointer).and_then(|s| s.as_object_mut()) {
+        resolver.taken.extend(schemas.keys().cloned());
+        // Claim the names of root schemas that are mere references to another file first
+        for (name, schema) in schemas.iter_mut() {
+            let Some(target) = external_target(schema, spec_path) else {
+                continue;
+            };
+            resolver.names.insert(target.clone(), name.clone());
+            match resolver.load(&target, true) {
+                Ok(target) => *schema = target,
+                // Reported when the reference is met again below
+                Err(_) => {
+                    resolver.names.remove(&target);
+                }
+            }
+        }
+    }
+
+    resolver.rewrite(spec, spec_path, true, false);
+    if verbose && resolver.misses > 0 {
+        eprintln!("  ↺ $ref file cache: {} read, {} reused", resolver.misses, resolver.hits);
+    }
+    if resolver.imported.is_empty() {
+        return Ok(());
+    }
+
+    let root = if swagger2 {
+        spec.as_object_mut().map(|spec| spec.entry("definitions"))
+    } else {
+        spec.as_object_mut()
+            .and_then(|spec| spec.entry("components").or_insert_with(|| Value::Object(Map::new())).as_object_mut())
+            .map(|components| components.entry("schemas"))
+    };
+    let Some(Value::Object(schemas)) = root.map(|entry| entry.or_insert_with(|| Value::Object(Map::new()))) else {
+        bail!("Cannot add the schemas referenced from other files: the schemas root is not an object");
+    };
+    schemas.extend(resolver.imported);
+    Ok(())
+}
+
+/// Files the spec at `spec_path` references, directly or through other referenced files,
+/// by canonical path. Unreadable files are listed but not followed.
+pub fn referenced_files(spec_path: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = Vec::new();
+    if let Ok(spec) = read_document(spec_path) {
+        collect_refs(&spec, spec_path, &mut pending);
+    }
+    while let Some(path) = pending.pop() {
+        if files.contains(&path) {
+            continue;
+        }
+        if let Ok(document) = read_document(&path) {
+            collect_refs(&document, &path, &mut pending);
+        }
+        files.push(path);
+    }
+    files
+}
+
+/// Add the files referenced below `value`, read from `file`, to `found`
+fn collect_refs(value: &Value, file: &Path, found: &mut Vec<PathBuf>) {
+    match value {
+        Value::Object(object) => {
+            if let Some((path, _)) = object.get("$ref").and_then(|v| v.as_str()).and_then(|r| split_ref(r, file)) {
+                found.push(path);
+            }
+            object.values().for_each(|child| collect_refs(child, file, found));
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_refs(item, file, found)),
+        _ => {}
+    }
+}
+
+/// A schema in another file: the canonical file path and the JSON pointer into it
+type Target = (PathBuf, String);
+
+struct Resolver {
+    /// Prefix of local `$ref`s to the schemas root, e.g. `#/components/schemas/`
+    ref_prefix: &'static str,
+    /// Parsed referenced files, by canonical path
+    documents: HashMap<PathBuf, Value>,
+    /// Name given to the copy of each referenced schema
+    names: HashMap<Target, String>,
+    /// Schema names already in use
+    taken: HashSet<String>,
+    /// Copies to add to the schemas root, in the order they were found
+    imported: Vec<(String, Value)>,
+    /// Report the cache lookups of referenced files
+    verbose: bool,
+    /// Lookups of a file already in `documents`
+    hits: usize,
+    /// Lookups that read a file
+    misses: usize,
+}
+
+impl Resolver {
+    fn new(ref_prefix: &'static str, verbose: bool) -> Self {
+        Resolver {
+            ref_prefix,
+            documents: HashMap::new(),
+            names: HashMap::new(),
+            taken: HashSet::new(),
+            imported: Vec::new(),
+            verbose,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Point every schema reference into another file below `value`, read from `file`, at the
+    /// copy of its target, and replace other ones by their target. Outside the main spec, local
+    /// references (`#/Address`) also point into another file. `in_schema` tells whether
+    /// `value` is (part of) a schema.
+    fn rewrite(&mut self, value: &mut Value, file: &Path, main: bool, in_schema: bool) {
+        match value {
+            Value::Object(object) => {
+                let target = match object.get("$ref").and_then(|v| v.as_str()) {
+                    Some(ref_str) if ref_str.starts_with('#') && !main => {
+                        Some((file.to_path_buf(), ref_str[1..].to_string()))
+                    }
+                    Some(ref_str) => split_ref(ref_str, file),
+                    None => None,
+                };
+                // Unreadable files are reported and their references left for the parser to skip
+                match target {
+                    Some(target) if in_schema => match self.import(target) {
+                        Ok(name) => {
+                            let pointer_segment = name.replace('~', "~0").replace('/', "~1");
+                            object.insert(
+                                "$ref".to_string(),
+                                Value::String(format!("{}{}", self.ref_prefix, pointer_segment))
+                            );
+                        }
+                        Err(err) => eprintln!("  ⚠ {:#}", err),
+                    },
+                    Some(target) => match self.load(&target, false) {
+                        Ok(target) => {
+                            *value = target;
+                            return;
+                        }
+                        Err(err) => eprintln!("  ⚠ {:#}", err),
+                    },
+                    None => {}
+                }
+                for (key, child) in object.iter_mut() {
+                    let in_schema = in_schema || matches!(key.as_str(), "schema" | "schemas" | "definitions");
+                    self.rewrite(child, file, main, in_schema);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.rewrite(item, file, main, in_schema);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Name of the copy of `target`, copying it on first use
+    fn import(&mut self, target: Target) -> Result<String> {
+        if let Some(name) = self.names.get(&target) {
+            return Ok(name.clone());
+        }
+        let name = self.unique_name(&target);
+        // Named before its own references are followed, so that cycles end at it
+        self.names.insert(target.clone(), name.clone());
+        self.taken.insert(name.clone());
+        match self.load(&target, true) {
+            Ok(schema) => {
+                self.imported.push((name.clone(), schema));
+                Ok(name)
+            }
+            Err(err) => {
+                self.names.remove(&target);
+                self.taken.remove(&name);
+                Err(err)
+            }
+        }
+    }
+
+    /// Copy of the value `target` points at, its own references rewritten
+    fn load(&mut self, target: &Target, in_schema: bool) -> Result<Value> {
+        let (path, pointer) = target;
+        if self.documents.contains_key(path) {
+            self.hits += 1;
+            if self.verbose {
+                eprintln!("  ↺ Reusing $ref file {}#{}", path.display(), pointer);
+            }
+        } else {
+            if self.verbose {
+                eprintln!("  ↓ Reading $ref file {}#{}", path.display(), pointer);
+            }
+            self.misses += 1;
+            self.documents.insert(path.clone(), read_document(path)?);
+        }
+        let Some(schema) = self.documents[path].pointer(pointer) else {
+            bail!("Cannot resolve $ref '{}#{}'", path.display(), pointer);
+        };
+        let mut schema = schema.clone();
+        self.rewrite(&mut schema, path, false, in_schema);
+        Ok(schema)
+    }
+
+    /// Last segment of the pointer (PascalCased when it is not a valid identifier), or the
+    /// file name for a whole file, prefixed with the file name and then numbered while taken
+    fn unique_name(&self, (path, pointer): &Target) -> String {
+        let file_name = to_pascal_identifier(&path.file_stem().unwrap_or_default().to_string_lossy());
+        let name = match pointer.rsplit('/').next() {
+            Some(segment) if !segment.is_empty() => {
+                let segment = segment.replace("~1", "/").replace("~0", "~");
+                if is_identifier(&segment) { segment } else { to_pascal_identifier(&segment) }
+            }
+            _ => file_name.clone(),
+        };
+        if !self.taken.contains(&name) {
+            return name;
+        }
+        let prefixed = format!("{}{}", file_name, name);
+        (1..)
+            .map(|n| if n == 1 { prefixed.clone() } else { format!("{}{}", prefixed, n) })
+            .find(|candidate| !self.taken.contains(candidate))
+            .unwrap()
+    }
+}
+
+/// Target of a schema of `file` that is only a `$ref` into another file
+fn external_target(schema: &Value, file: &Path) -> Option<Target> {
+    let object = schema.as_object().filter(|object| object.len() == 1)?;
+    split_ref(object.get("$ref")?.as_str()?, file)
+}
+
+/// Split a `$ref` of the file `from` into another file into that file, resolved against the
+/// directory of `from`, and the pointer. `None` for local and remote references.
+fn split_ref(ref_str: &str, from: &Path) -> Option<Target> {
+    if ref_str.starts_with('#') || super::is_remote(ref_str) {
+        return None;
+    }
+    let (file, pointer) = ref_str.split_once('#').unwrap_or((ref_str, ""));
+    let path = from.parent().unwrap_or(Path::new("")).join(file);
+    Some((fs::canonicalize(&path).unwrap_or(path), pointer.to_string()))
+}
+
+/// Parse a referenced JSON file
+fn read_document(path: &Path) -> Result<Value> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read $ref file: {}", path.display()))?;
+    if !super::looks_like_json(content.as_bytes()) {
+        return super::parse_yaml_or_json(&path.display().to_string(), &content);
+    }
+    serde_json::from_str(&content).with_context(|| format!("Invalid JSON in $ref file: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Write `files` into a fresh temporary directory, returning its path
+    fn write_files(name: &str, files: &[(&str, Value)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ropenapi-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            fs::write(dir.join(file), content.to_string()).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn referenced_files_are_read_once() {
+        let common = json!({
+            "Address": { "type": "object", "properties": { "country": { "$ref": "#/Country" } } },
+            "Country": { "type": "string" },
+        });
+        let dir = write_files("cache", &[("common.json", common)]);
+        let file = fs::canonicalize(dir.join("common.json")).unwrap();
+
+        let mut resolver = Resolver::new("#/components/schemas/", false);
+        let address = resolver.load(&(file.clone(), "/Address".to_string()), true).unwrap();
+        assert_eq!(address["properties"]["country"]["$ref"], "#/components/schemas/Country");
+        resolver.load(&(file, "/Country".to_string()), true).unwrap();
+        // Read for `Address`, then reused for its reference to `Country` and the lookup above
+        assert_eq!((resolver.misses, resolver.hits), (1, 2));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn escaped_pointer_segments_are_named_as_identifiers() {
+        let common = json!({ "Foo/Bar": { "type": "string" } });
+        let dir = write_files("escaped", &[("common.json", common)]);
+        let file = fs::canonicalize(dir.join("common.json")).unwrap();
+
+        let mut resolver = Resolver::new("#/components/schemas/", false);
+        let name = resolver.import((file, "/Foo~1Bar".to_string())).unwrap();
+        assert_eq!(name, "FooBar");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn referenced_files_are_found_through_other_files() {
+        let spec = json!({
+            "openapi": "3.0.0",
+            "paths": {},
+            "components": { "schemas": { "User": { "$ref": "./user.json#/User" }, "Id": { "$ref": "#/components/schemas/User" } } }
+        });
+        let user = json!({ "User": { "type": "object", "properties": { "address": { "$ref": "./common.json#/Address" } } } });
+        let common = json!({ "Address": { "type": "string" } });
+        let dir = write_files("referenced", &[("api.json", spec), ("user.json", user), ("common.json", common)]);
+
+        let mut files = referenced_files(&dir.join("api.json"));
+        files.sort();
+        let canonical = |name: &str| fs::canonicalize(dir.join(name)).unwrap();
+        assert_eq!(files, [canonical("common.json"), canonical("user.json")]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,64 @@
+//! `--template` text of the generated functions, with `{{placeholder}}` substitution
+
+/// Built-in template of a request function
+pub const FUNCTION_TEMPLATE: &str =
+    "{{doc}}export const {{name}} = async ({{params}}): Promise<{{returnType}}> => {{body}};";
+
+/// Placeholders a function template can use
+pub const PLACEHOLDERS: &[&str] = &[
+    // JSDoc block followed by a newline, or nothing
+    "doc",
+    "name",
+    "params",
+    "returnType",
+    // `{ ... }` block sending the request
+    "body",
+    "method",
+    "path",
+    "url",
+    "operationId",
+    "summary",
+];
+
+/// Read a function template, rejecting unknown placeholders
+pub fn load(path: &str) -> Result<String, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    for name in placeholders(&text) {
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(format!("unknown placeholder {{{{{}}}}} in {}, expected one of: {}", name, path, PLACEHOLDERS.join(", ")));
+        }
+    }
+    Ok(text)
+}
+
+/// Substitute the `{{name}}` (or `{{ name }}`) placeholders of `template` with `values`
+pub fn render(template: &str, values: &[(&str, String)]) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some((before, name, after)) = next_placeholder(rest) {
+        out.push_str(before);
+        if let Some((_, value)) = values.iter().find(|(key, _)| *key == name) {
+            out.push_str(value);
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some((_, name, after)) = next_placeholder(rest) {
+        names.push(name);
+        rest = after;
+    }
+    names
+}
+
+/// Split `text` around its first placeholder: the text before it, its name and the text after it
+fn next_placeholder(text: &str) -> Option<(&str, &str, &str)> {
+    let (before, rest) = text.split_once("{{")?;
+    let (name, after) = rest.split_once("}}")?;
+    Some((before, name.trim(), after))
+}
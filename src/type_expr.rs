@@ -0,0 +1,67 @@
+/// Inner text of `expr` when it is entirely wrapped in `open` ... `close`
+pub fn enclosed(expr: &str, open: char, close: char) -> Option<&str> {
+    let inner = expr.strip_prefix(open)?.strip_suffix(close)?;
+    // `(A) | (B)` starts and ends with the delimiters without being wrapped in them
+    if depth_never_closes(inner) {
+        Some(inner.trim())
+    } else {
+        None
+    }
+}
+
+/// Whether brackets in `text` never close below its starting depth
+fn depth_never_closes(text: &str) -> bool {
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut prev = '\0';
+    for c in text.chars() {
+        if in_string {
+            in_string = !(c == '\'' && prev != '\\');
+        } else {
+            match c {
+                '\'' => in_string = true,
+                '(' | '{' | '[' | '<' => depth += 1,
+                ')' | '}' | ']' | '>' => depth -= 1,
+                _ => {}
+            }
+            if depth < 0 {
+                return false;
+            }
+        }
+        prev = c;
+    }
+    true
+}
+
+/// Whether `expr` is a single-quoted string literal
+pub fn is_string_literal(expr: &str) -> bool {
+    expr.len() >= 2 && expr.starts_with('\'') && expr.ends_with('\'')
+}
+
+/// Split on `separator` outside of brackets and string literals, trimming the parts
+pub fn split_top_level(expr: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut prev = '\0';
+    let mut start = 0;
+    for (idx, c) in expr.char_indices() {
+        if in_string {
+            in_string = !(c == '\'' && prev != '\\');
+        } else {
+            match c {
+                '\'' => in_string = true,
+                '(' | '{' | '[' | '<' => depth += 1,
+                ')' | '}' | ']' | '>' => depth -= 1,
+                c if c == separator && depth == 0 => {
+                    parts.push(expr[start..idx].trim());
+                    start = idx + c.len_utf8();
+                }
+                _ => {}
+            }
+        }
+        prev = c;
+    }
+    parts.push(expr[start..].trim());
+    parts
+}
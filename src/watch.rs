@@ -0,0 +1,126 @@
+//! `--watch`: regenerate the services whenever a spec changes, until interrupted.
+//! Local files, and the files their `$ref`s point into, are polled for modification time
+//! changes. When any spec is remote, all specs are re-read on an interval instead and
+//! regenerated when their content differs.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::thread;
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+
+use crate::cli::Args;
+use crate::parser::{ is_remote, referenced_files };
+
+/// How often a local spec file is checked for changes
+const FILE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How long a changed file has to stay unchanged before regenerating, so editors that
+/// write in several steps trigger a single run
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// How often a remote spec is fetched again
+const REMOTE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+pub fn run(args: &Args) -> Result<()> {
+    if args.swagger.iter().any(|source| is_remote(source)) {
+        watch_remote(args)
+    } else {
+        watch_file(args)
+    }
+}
+
+fn watch_file(args: &Args) -> Result<()> {
+    let mut paths = watched_paths(args);
+    let mut last_modified = modified(&paths);
+    regenerate(args, None);
+    println!("Watching {} for changes (Ctrl+C to stop)", args.swagger.join(", "));
+
+    loop {
+        thread::sleep(FILE_POLL_INTERVAL);
+        if modified(&paths) == last_modified {
+            continue;
+        }
+
+        // Wait for the writes to settle before regenerating
+        let mut settled = modified(&paths);
+        loop {
+            thread::sleep(DEBOUNCE);
+            let current = modified(&paths);
+            if current == settled {
+                break;
+            }
+            settled = current;
+        }
+        // The change may have added or removed `$ref`s to other files
+        paths = watched_paths(args);
+        last_modified = modified(&paths);
+        regenerate(args, None);
+    }
+}
+
+/// The spec files followed by the files they reference
+fn watched_paths(args: &Args) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = args.swagger.iter().map(PathBuf::from).collect();
+    for spec in &args.swagger {
+        for file in referenced_files(Path::new(spec)) {
+            if !paths.contains(&file) {
+                paths.push(file);
+            }
+        }
+    }
+    paths
+}
+
+fn watch_remote(args: &Args) -> Result<()> {
+    let mut last = regenerate(args, None);
+    println!(
+        "Polling {} every {}s for changes (Ctrl+C to stop)",
+        args.swagger.join(", "),
+        REMOTE_POLL_INTERVAL.as_secs()
+    );
+
+    loop {
+        thread::sleep(REMOTE_POLL_INTERVAL);
+        if let Some(specs) = regenerate(args, last.as_ref()) {
+            last = Some(specs);
+        }
+    }
+}
+
+/// Read the specs and, unless they equal `previous`, regenerate the services.
+/// Errors are reported without stopping the watch. Returns the specs that were read.
+fn regenerate(args: &Args, previous: Option<&Vec<Value>>) -> Option<Vec<Value>> {
+    let specs = match crate::read_specs(args) {
+        Ok(specs) => specs,
+        Err(err) => {
+            eprintln!("[{}] ✗ {:#}", timestamp(), err);
+            return None;
+        }
+    };
+    if previous == Some(&specs) {
+        return Some(specs);
+    }
+
+    match crate::generate(args, &specs) {
+        Ok(()) => println!("[{}] ✓ Regenerated from {}", timestamp(), args.swagger.join(", ")),
+        Err(err) => eprintln!("[{}] ✗ {:#}", timestamp(), err),
+    }
+    Some(specs)
+}
+
+/// Modification times of the watched files
+fn modified(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Current UTC time of day as `HH:MM:SS`
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    format!("{:02}:{:02}:{:02}", (secs / 3600) % 24, (secs / 60) % 60, secs % 60)
+}